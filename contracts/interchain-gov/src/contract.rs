@@ -6,8 +6,10 @@ use crate::{
 };
 
 use crate::dependencies::IBC_CLIENT_DEP;
-use crate::ibc_callbacks::{FINALIZE_CALLBACK_ID, PROPOSE_CALLBACK_ID, REGISTER_VOTE_ID};
-use crate::msg::InterchainGovSudoMsg;
+use crate::ibc_callbacks::{
+    FINALIZE_CALLBACK_ID, PROPOSE_CALLBACK_ID, REGISTER_VOTE_ID, SYNC_CHECK_ID,
+};
+use crate::msg::{InterchainGovMigrateMsg, InterchainGovSudoMsg};
 use abstract_adapter::AdapterContract;
 use cosmwasm_std::{Empty, Response};
 
@@ -32,6 +34,7 @@ const INTERCHAIN_GOV: InterchainGov = InterchainGov::new(MY_ADAPTER_ID, ADAPTER_
         (PROPOSE_CALLBACK_ID, ibc_callbacks::proposal_callback),
         (FINALIZE_CALLBACK_ID, ibc_callbacks::finalize_callback),
         (REGISTER_VOTE_ID, ibc_callbacks::vote_result_callback),
+        (SYNC_CHECK_ID, ibc_callbacks::sync_check_callback),
     ])
     // WE need a reply for every member
     .with_replies(&[
@@ -42,6 +45,7 @@ const INTERCHAIN_GOV: InterchainGov = InterchainGov::new(MY_ADAPTER_ID, ADAPTER_
         (4, replies::icq_reply),
         (5, replies::icq_reply),
         (6, replies::icq_reply),
+        (replies::TRANSFER_REPLY_ID, replies::transfer_reply),
     ])
     .with_sudo(handlers::sudo_handler)
     .with_dependencies(&[IBC_CLIENT_DEP]);
@@ -50,6 +54,17 @@ const INTERCHAIN_GOV: InterchainGov = InterchainGov::new(MY_ADAPTER_ID, ADAPTER_
 #[cfg(feature = "export")]
 abstract_adapter::export_endpoints!(INTERCHAIN_GOV, InterchainGov);
 
+// Adapters don't get a migrate endpoint from abstract, the state migrations need one
+#[cfg(feature = "export")]
+#[cosmwasm_std::entry_point]
+pub fn migrate(
+    deps: cosmwasm_std::DepsMut,
+    env: cosmwasm_std::Env,
+    msg: InterchainGovMigrateMsg,
+) -> AdapterResult {
+    handlers::migrate_handler(deps, env, INTERCHAIN_GOV, msg)
+}
+
 abstract_adapter::cw_orch_interface!(
     INTERCHAIN_GOV,
     InterchainGov,
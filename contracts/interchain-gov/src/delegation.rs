@@ -0,0 +1,64 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::{Order, StdResult, Storage};
+
+use crate::state::{CountedVote, Proposal, Vote, DELEGATIONS};
+
+/// Votes of the members as counted in the tally.
+///
+/// Members that didn't vote, None, get the vote of their delegate if the delegation covers the
+/// proposal and the delegate voted itself. Delegated votes aren't passed on again, members left
+/// without a vote count as [`Vote::NoVote`].
+pub(crate) fn counted_votes(
+    storage: &dyn Storage,
+    prop: &Proposal,
+    votes: Vec<(ChainName, Option<Vote>)>,
+) -> StdResult<Vec<CountedVote>> {
+    votes
+        .iter()
+        .map(|(chain, vote)| {
+            if let Some(vote) = vote {
+                return Ok(CountedVote {
+                    chain: chain.clone(),
+                    vote: vote.clone(),
+                    delegate: None,
+                });
+            }
+            let delegated = DELEGATIONS
+                .may_load(storage, chain)?
+                .filter(|delegation| delegation.covers(prop))
+                .and_then(|delegation| {
+                    votes.iter().find_map(|(voter, vote)| match vote {
+                        Some(vote) if voter == &delegation.delegate => {
+                            Some((voter.clone(), vote.clone()))
+                        }
+                        _ => None,
+                    })
+                });
+            Ok(match delegated {
+                Some((delegate, vote)) => CountedVote {
+                    chain: chain.clone(),
+                    vote,
+                    delegate: Some(delegate),
+                },
+                None => CountedVote {
+                    chain: chain.clone(),
+                    vote: Vote::NoVote,
+                    delegate: None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Drop the delegations from and to a member that left the governance
+pub(crate) fn remove_delegations(storage: &mut dyn Storage, member: &ChainName) -> StdResult<()> {
+    let delegations = DELEGATIONS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (delegator, delegation) in delegations {
+        if &delegator == member || &delegation.delegate == member {
+            DELEGATIONS.remove(storage, &delegator);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use abstract_adapter::sdk::{Execution, TransferInterface};
+use abstract_adapter::traits::AccountIdentification;
+use cosmwasm_std::{
+    to_json_binary, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, DistributionMsg, Env, Storage,
+    WasmMsg,
+};
+use cw_asset::{Asset, AssetInfo};
+
+use crate::contract::{AdapterResult, InterchainGov};
+use crate::state::{
+    load_gov_config, Deposit, DepositForfeit, Proposal, ProposalId, ProposalOutcome, DEPOSITS,
+};
+use crate::InterchainGovError;
+
+/// Move the configured deposit from the proposing account to the adapter.
+///
+/// Returns no messages if no deposit is required.
+pub(crate) fn take_deposit(
+    deps: DepsMut,
+    env: &Env,
+    app: &InterchainGov,
+    prop_id: &ProposalId,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let Some(config) = load_gov_config(deps.storage)?.deposit else {
+        return Ok(vec![]);
+    };
+
+    let transfer = app
+        .bank(deps.as_ref())
+        .transfer(vec![config.asset.clone()], &env.contract.address)?;
+    let msg = app.executor(deps.as_ref()).execute(vec![transfer])?;
+
+    let depositor = app.proxy_address(deps.as_ref())?;
+    DEPOSITS.save(
+        deps.storage,
+        prop_id.clone(),
+        &Deposit { depositor, config },
+    )?;
+    Ok(vec![msg.into()])
+}
+
+/// Settle the deposit of a proposal that got an outcome.
+///
+/// The deposit is refunded if the proposal passed or reached the quorum, else it is forfeited.
+/// Vetoed proposals always forfeit their deposit.
+/// Only the proposing chain holds a deposit, other members get no messages.
+pub(crate) fn settle_deposit(
+    storage: &mut dyn Storage,
+    prop_id: &ProposalId,
+    prop: &Proposal,
+    outcome: &ProposalOutcome,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let Some(deposit) = DEPOSITS.may_load(storage, prop_id.clone())? else {
+        return Ok(vec![]);
+    };
+
+    let voted = outcome.votes_for + outcome.votes_against - outcome.no_votes;
+    // Proposals without members never reach the quorum
+    let quorum_reached = deposit.config.quorum.is_some_and(|quorum| {
+        Decimal::checked_from_ratio(voted, prop.members.members.len() as u128)
+            .is_ok_and(|turnout| turnout >= quorum)
+    });
+    let forfeit = if outcome.vetoed.is_none() && (outcome.passed || quorum_reached) {
+        DepositForfeit::Refund
+    } else {
+        deposit.config.forfeit.clone()
+    };
+    release_deposit(storage, prop_id, forfeit)
+}
+
+/// Refund the deposit of a proposal that was dropped before it got an outcome
+pub(crate) fn refund_deposit(
+    storage: &mut dyn Storage,
+    prop_id: &ProposalId,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    release_deposit(storage, prop_id, DepositForfeit::Refund)
+}
+
+fn release_deposit(
+    storage: &mut dyn Storage,
+    prop_id: &ProposalId,
+    forfeit: DepositForfeit,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let Some(Deposit { depositor, config }) = DEPOSITS.may_load(storage, prop_id.clone())? else {
+        return Ok(vec![]);
+    };
+    DEPOSITS.remove(storage, prop_id.clone());
+
+    let msg = match forfeit {
+        DepositForfeit::Refund => config.asset.transfer_msg(depositor)?,
+        DepositForfeit::Burn => burn_msg(config.asset)?,
+        DepositForfeit::CommunityPool => DistributionMsg::FundCommunityPool {
+            amount: vec![native_coin(config.asset)?],
+        }
+        .into(),
+    };
+    Ok(vec![msg])
+}
+
+fn burn_msg(asset: Asset) -> AdapterResult<CosmosMsg> {
+    match &asset.info {
+        AssetInfo::Cw20(contract_addr) => Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_json_binary(&cw20::Cw20ExecuteMsg::Burn {
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }
+        .into()),
+        _ => Ok(BankMsg::Burn {
+            amount: vec![native_coin(asset)?],
+        }
+        .into()),
+    }
+}
+
+fn native_coin(asset: Asset) -> AdapterResult<Coin> {
+    match asset.info {
+        AssetInfo::Native(denom) => Ok(Coin::new(asset.amount.u128(), denom)),
+        info => Err(InterchainGovError::InvalidDeposit(format!(
+            "{info} is not a native asset"
+        ))),
+    }
+}
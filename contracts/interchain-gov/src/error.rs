@@ -3,9 +3,10 @@ use abstract_adapter::objects::chain_name::ChainName;
 use abstract_adapter::objects::module::ModuleInfo;
 
 use abstract_adapter::{sdk::AbstractSdkError, std::AbstractError, AdapterError};
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, StdError};
 use cw_asset::AssetError;
 use cw_controllers::AdminError;
+use cw_utils::Expiration;
 use ibc_sync_state::{DataState, SyncStateError};
 use thiserror::Error;
 
@@ -27,7 +28,7 @@ pub enum InterchainGovError {
     Admin(#[from] AdminError),
 
     #[error("{0}")]
-    SyncState(#[from] SyncStateError),
+    SyncState(SyncStateError),
 
     #[error("{0}")]
     AdapterError(#[from] AdapterError),
@@ -41,12 +42,30 @@ pub enum InterchainGovError {
     #[error("Proposal Already exists")]
     ProposalAlreadyExists(ProposalId),
 
+    #[error("Data {key} not found")]
+    DataNotFound { key: String },
+
     #[error("Data {key} not finalized. Status: {state}")]
     DataNotFinalized { key: String, state: DataState },
 
+    #[error("Data {key} not proposed. Status: {state:?}")]
+    DataNotProposed {
+        key: String,
+        state: Option<DataState>,
+    },
+
     #[error("Member {member} already exists")]
     MemberAlreadyExists { member: String },
 
+    #[error("{member} is not a member of the governance")]
+    UnknownMember { member: ChainName },
+
+    #[error("Not part of an interchain governance")]
+    NoGovernance,
+
+    #[error("No pending invite from {inviter}")]
+    InviteNotFound { inviter: ChainName },
+
     #[error("Remote {host} not available")]
     UnknownRemoteHost { host: String },
 
@@ -109,6 +128,9 @@ pub enum InterchainGovError {
     #[error("Votes already finalized {0}")]
     VotesAlreadyFinalized(String),
 
+    #[error("Proposal {0} was already executed")]
+    ProposalExecuted(ProposalId),
+
     #[error("Gov votes already queried {0}")]
     GovVotesAlreadyQueried(String),
 
@@ -122,4 +144,90 @@ pub enum InterchainGovError {
         chain: ChainName,
         prop_id: ProposalId,
     },
+
+    #[error("Invalid proposal deposit: {0}")]
+    InvalidDeposit(String),
+
+    #[error("Treasury of {chain} holds less than {amount}")]
+    InsufficientTreasury { chain: ChainName, amount: Coin },
+
+    #[error("No account is governed by this adapter")]
+    NoGoverningAccount,
+
+    #[error("Can't transfer from {chain} to itself")]
+    SameChainTransfer { chain: ChainName },
+
+    #[error("{chain} doesn't report the result of ICS-20 transfers to the adapter")]
+    TransferCallbacksUnsupported { chain: ChainName },
+
+    #[error("Invalid governance config: {0}")]
+    InvalidConfig(String),
+
+    #[error("Proposals can't {action}")]
+    ActionNotAllowed { action: String },
+
+    #[error("The governance is limited to {max} members")]
+    TooManyMembers { max: u32 },
+
+    #[error("Invalid adapter version {version}: {error}")]
+    InvalidVersion { version: String, error: String },
+
+    #[error("Adapter version {version} is incompatible with {expected}")]
+    IncompatibleVersion { version: String, expected: String },
+
+    #[error("Proposal {0} is waiting on its timelock")]
+    ProposalQueued(ProposalId),
+
+    #[error("Proposal {0} is not queued")]
+    ProposalNotQueued(ProposalId),
+
+    #[error("Timelock of {prop_id} expires at {executable_at}")]
+    TimelockNotExpired {
+        prop_id: ProposalId,
+        executable_at: Expiration,
+    },
+
+    #[error("Timelock of {0} expired, it can't be vetoed anymore")]
+    TimelockExpired(ProposalId),
+
+    #[error("Emergency proposals are disabled, the config has no emergency threshold")]
+    EmergencyDisabled,
+
+    #[error("Emergency proposals can't {action}")]
+    EmergencyNotAllowed { action: String },
+
+    #[error("Proposal has to stay open for the voting period, at least until {min_voting_period}")]
+    VotingPeriodTooShort { min_voting_period: Expiration },
+
+    #[error("Invalid proposal options: {0}")]
+    InvalidChoices(String),
+
+    #[error("Vote doesn't match the options of {0}")]
+    InvalidVote(ProposalId),
+
+    #[error("Governance is paused: {reason}")]
+    Paused { reason: String },
+
+    #[error("Can't migrate while syncing {what}")]
+    MigrateWhileSyncing { what: String },
+
+    #[error("Packet built on state hash {actual}, local state hash is {expected}")]
+    StateHashMismatch { expected: String, actual: String },
+}
+
+/// Sync state errors callers can react to get their own variant
+impl From<SyncStateError> for InterchainGovError {
+    fn from(error: SyncStateError) -> Self {
+        match error {
+            SyncStateError::Std(error) => InterchainGovError::Std(error),
+            SyncStateError::NotFound { key } => InterchainGovError::DataNotFound { key },
+            SyncStateError::DataNotFinalized { key, state } => {
+                InterchainGovError::DataNotFinalized { key, state }
+            }
+            SyncStateError::DataNotProposed { key, state } => {
+                InterchainGovError::DataNotProposed { key, state }
+            }
+            error => InterchainGovError::SyncState(error),
+        }
+    }
 }
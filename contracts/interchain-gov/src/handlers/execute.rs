@@ -4,23 +4,40 @@ use abstract_adapter::sdk::{AbstractSdkResult, IbcInterface};
 use abstract_adapter::std::ibc::CallbackInfo;
 use abstract_adapter::std::AbstractResult;
 use abstract_adapter::traits::AbstractResponse;
+use abstract_adapter::traits::AccountIdentification;
 use abstract_adapter::traits::ModuleIdentification;
-use base64::Engine;
 use cosmwasm_std::{
-    to_json_binary, CosmosMsg, DepsMut, Env, MessageInfo, Order, StdResult, Storage, WasmQuery,
+    to_json_binary, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, StdResult,
+    Storage, SubMsg, Uint128, WasmQuery,
 };
 
-use ibc_sync_state::DataState;
+use dao_voting::threshold::Threshold;
+use dao_voting::voting::does_vote_count_pass;
+use ibc_sync_state::{DataState, Packet, SyncEvent, TwoPhaseMsg};
 
-use crate::ibc_callbacks::{FINALIZE_CALLBACK_ID, PROPOSE_CALLBACK_ID, REGISTER_VOTE_ID};
+use crate::delegation::counted_votes;
+use crate::deposit::{settle_deposit, take_deposit};
+use crate::ibc_callbacks::{
+    FINALIZE_CALLBACK_ID, PROPOSE_CALLBACK_ID, REGISTER_VOTE_ID, SYNC_CHECK_ID,
+};
+use crate::membership::{invite_members, join_members, remove_member, reset_membership};
 use crate::msg::InterchainGovQueryMsg;
-use crate::msg::{InterchainGovIbcCallbackMsg, InterchainGovIbcMsg};
+use crate::msg::{InterchainGovIbcCallbackMsg, InterchainGovIbcMsg, InterchainGovIbcPacket};
+use crate::pause::{apply_pause_action, assert_not_paused, load_guardian};
 use crate::state::{
-    Governance, GovernanceVote, Members, Proposal, ProposalAction, ProposalId, ProposalMsg,
-    ProposalOutcome, TallyResult, Vote, ALLOW_JOINING_GOV, FINALIZED_PROPOSALS, GOV_VOTE_QUERIES,
-    MEMBERS, MEMBERS_STATE_SYNC, PROPOSAL_STATE_SYNC, TEMP_REMOTE_GOV_MODULE_ADDRS, VOTE,
-    VOTE_RESULTS,
+    load_gov_config, Authority, Delegation, GovConfig, Governance, GovernanceVote, Members, Pause,
+    Proposal, ProposalAction, ProposalId, ProposalMsg, ProposalOutcome, QueuedProposal,
+    StateCommit, SyncReport, TallyResult, Veto, Vote, VoteResult, ALLOW_JOINING_GOV, DELEGATIONS,
+    FINALIZED_PROPOSALS, GOV_CONFIG_SYNC, GOV_VOTE_QUERIES, MEMBERS, MEMBERS_STATE_SYNC, PAUSE,
+    PENDING_INVITES, PROPOSAL_STATE_SYNC, PROPOSAL_TWO_PHASE, QUEUED_PROPOSALS, STATE_HASH,
+    SYNC_REPORTS, TEMP_REMOTE_GOV_MODULE_ADDRS, VOTE, VOTE_RESULTS,
+};
+use crate::state_hash::{commit_state, load_state_hash};
+use crate::treasury::{
+    assert_transfer_callbacks, assert_treasury_funds, governing_account, ibc_transfer,
+    record_deposit, spend_from_treasury, treasury_balance,
 };
+use crate::veto::{update_guardians, veto_by_proposal, veto_queued};
 use crate::{
     contract::{AdapterResult, InterchainGov},
     msg::InterchainGovExecuteMsg,
@@ -59,10 +76,32 @@ pub fn execute_handler(
             temporary_register_remote_gov_module_addrs(deps, adapter, modules)
         }
         InterchainGovExecuteMsg::Execute { prop_id } => execute_prop(deps, env, adapter, prop_id),
+        InterchainGovExecuteMsg::ExecuteQueued { prop_id } => {
+            execute_queued(deps, env, adapter, prop_id)
+        }
+        InterchainGovExecuteMsg::Veto { prop_id, reason } => {
+            veto(deps, env, info, adapter, prop_id, reason)
+        }
+        InterchainGovExecuteMsg::Pause { reason } => pause(deps, env, info, adapter, reason),
         InterchainGovExecuteMsg::SetAcceptGovInvite { members } => {
             ALLOW_JOINING_GOV.save(deps.storage, &members)?;
             Ok(adapter.response("set_accept_gov_invite"))
         }
+        InterchainGovExecuteMsg::AcceptInvite { inviter } => {
+            respond_to_invite(deps, env, adapter, inviter, true)
+        }
+        InterchainGovExecuteMsg::RejectInvite { inviter } => {
+            respond_to_invite(deps, env, adapter, inviter, false)
+        }
+        InterchainGovExecuteMsg::SyncCheck {} => sync_check(deps, env, adapter),
+        InterchainGovExecuteMsg::LeaveGovernance {} => leave_governance(deps, env, adapter),
+        InterchainGovExecuteMsg::DepositToTreasury { funds } => {
+            deposit_to_treasury(deps, env, adapter, funds)
+        }
+        InterchainGovExecuteMsg::Delegate { delegate, actions } => {
+            delegate_vote(deps, env, adapter, Some(Delegation { delegate, actions }))
+        }
+        InterchainGovExecuteMsg::Undelegate {} => delegate_vote(deps, env, adapter, None),
         _ => todo!(),
     }
 }
@@ -74,11 +113,19 @@ fn execute_prop(
     app: InterchainGov,
     prop_id: String,
 ) -> Result<cosmwasm_std::Response, InterchainGovError> {
+    // Executing it again would repeat its action
+    if FINALIZED_PROPOSALS.has(deps.storage, prop_id.clone()) {
+        return Err(InterchainGovError::ProposalExecuted(prop_id));
+    }
+    if QUEUED_PROPOSALS.has(deps.storage, prop_id.clone()) {
+        return Err(InterchainGovError::ProposalQueued(prop_id));
+    }
+
     // check existing vote results
     let existing_vote_results = VOTE_RESULTS
         .prefix(prop_id.clone())
         .range(deps.storage, None, None, Order::Ascending)
-        .collect::<StdResult<Vec<(ChainName, Option<GovernanceVote>)>>>()?;
+        .collect::<StdResult<Vec<(ChainName, VoteResult)>>>()?;
 
     // If we don't have any vote results, we need to query them
     if existing_vote_results.is_empty() {
@@ -87,7 +134,10 @@ fn execute_prop(
         });
     } else {
         // if we have pending votes, check they're all resolved
-        if existing_vote_results.iter().any(|(_, vote)| vote.is_none()) {
+        if existing_vote_results
+            .iter()
+            .any(|(_, vote)| vote.is_pending())
+        {
             return Err(InterchainGovError::VotesStillPending {
                 prop_id: prop_id.clone(),
                 chains: existing_vote_results
@@ -98,129 +148,477 @@ fn execute_prop(
         }
     }
 
-    let mut votes_for: u8 = 0;
-    let mut votes_against: u8 = 0;
-
-    let this_vote = VOTE.load(deps.storage, prop_id.clone())?;
-    if this_vote.vote == Vote::Yes {
-        votes_for += 1;
-    } else {
-        votes_against += 1;
+    let prop = PROPOSAL_STATE_SYNC.load(deps.storage, prop_id.clone())?.0;
+    assert_not_paused(deps.storage, Some(&prop))?;
+    // The other members only accept the outcome from the proposing chain
+    let our_chain = ChainName::new(&env);
+    if prop.proposer_chain != our_chain {
+        return Err(InterchainGovError::WrongChain {
+            expected: prop.proposer_chain,
+            actual: our_chain,
+        });
     }
 
-    // Then get prop and check if it passed
-    existing_vote_results
-        .iter()
-        .for_each(|(_, vote)| match vote {
-            Some(vote) => {
-                if vote.vote == Vote::Yes {
-                    votes_for += 1;
-                } else {
-                    votes_against += 1;
-                }
+    // Only members from the proposal's snapshot get to vote
+    let mut votes = vec![];
+    if prop.members.members.contains(&our_chain) {
+        let vote = VOTE.may_load(deps.storage, prop_id.clone())?;
+        votes.push((our_chain, vote.map(|vote| vote.vote)));
+    }
+    votes.extend(
+        existing_vote_results
+            .into_iter()
+            .filter(|(chain, _)| prop.members.members.contains(chain))
+            .map(|(chain, result)| (chain, result.vote())),
+    );
+    // Members that didn't vote count with their delegate
+    let votes = counted_votes(deps.storage, &prop, votes)?
+        .into_iter()
+        .map(|counted| counted.vote)
+        .collect::<Vec<_>>();
+
+    // Then check if it passed
+    let outcome = match &prop.choices {
+        Some(choices) => choices.tally(&votes),
+        None => {
+            let votes_for = votes.iter().filter(|vote| **vote == Vote::Yes).count() as u32;
+            let votes_against = votes.len() as u32 - votes_for;
+            let no_votes = votes.iter().filter(|vote| **vote == Vote::NoVote).count() as u32;
+            ProposalOutcome {
+                passed: threshold_passed(&prop.threshold, votes_for, votes_against, no_votes),
+                votes_for,
+                votes_against,
+                no_votes,
+                action_result: None,
+                vetoed: None,
+                tallies: vec![],
+                winner: None,
             }
-            None => panic!("Vote Null checked before."),
+        }
+    };
+
+    // Emergency proposals are tallied early, they only fail once expired
+    if prop.emergency && !outcome.passed && !prop.expiration.is_expired(&env.block) {
+        return Err(InterchainGovError::ProposalStillOpen(prop_id));
+    }
+
+    // Passed proposals wait for the timelock before their action is executed, emergencies don't
+    let timelock = load_gov_config(deps.storage)?
+        .timelock
+        .filter(|_| !prop.emergency);
+    if let (true, Some(timelock)) = (outcome.passed, timelock) {
+        let executable_at = timelock.after(&env.block);
+        QUEUED_PROPOSALS.save(
+            deps.storage,
+            prop_id.clone(),
+            &QueuedProposal {
+                outcome,
+                executable_at,
+            },
+        )?;
+        return Ok(app
+            .response("queue_proposal")
+            .add_attribute("prop_id", prop_id)
+            .add_attribute("executable_at", executable_at.to_string()));
+    }
+
+    finish_prop(deps, env, app, prop_id, prop, outcome)
+}
+
+/// Execute a passed proposal once its timelock expired
+fn execute_queued(
+    deps: DepsMut,
+    env: Env,
+    app: InterchainGov,
+    prop_id: ProposalId,
+) -> AdapterResult {
+    let Some(queued) = QUEUED_PROPOSALS.may_load(deps.storage, prop_id.clone())? else {
+        return Err(InterchainGovError::ProposalNotQueued(prop_id));
+    };
+    if !queued.executable_at.is_expired(&env.block) {
+        return Err(InterchainGovError::TimelockNotExpired {
+            prop_id,
+            executable_at: queued.executable_at,
         });
+    }
+    let prop = PROPOSAL_STATE_SYNC.load(deps.storage, prop_id.clone())?.0;
+    assert_not_paused(deps.storage, Some(&prop))?;
+    QUEUED_PROPOSALS.remove(deps.storage, prop_id.clone());
 
-    let outcome = if votes_for > votes_against {
-        ProposalOutcome {
-            passed: true,
-            votes_for,
-            votes_against,
-        }
-    } else {
-        ProposalOutcome {
-            passed: false,
-            votes_for,
-            votes_against,
-        }
+    finish_prop(deps, env, app, prop_id, prop, queued.outcome)
+}
+
+/// Veto a passed proposal as a guardian of this chain
+fn veto(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    app: InterchainGov,
+    prop_id: ProposalId,
+    reason: String,
+) -> AdapterResult {
+    let our_chain = ChainName::new(&env);
+    let guardian = load_guardian(deps.storage, &our_chain, info.sender.as_str())?;
+    let veto = Veto {
+        by: Authority::Guardian(guardian.clone()),
+        reason,
     };
 
     let prop = PROPOSAL_STATE_SYNC.load(deps.storage, prop_id.clone())?.0;
+    if prop.proposer_chain == our_chain {
+        let (packets, deposit_msgs) = veto_queued(deps.storage, &env, prop_id.clone(), veto)?;
+        let msgs = send_packets(deps.as_ref(), &app, packets)?;
+        return Ok(app
+            .response("veto")
+            .add_messages(msgs)
+            .add_messages(deposit_msgs)
+            .add_attribute("prop_id", prop_id)
+            .add_attribute("guardian_chain", guardian.chain.to_string())
+            .add_attribute("guardian", guardian.address));
+    }
+
+    // Only the proposing chain knows the timelock, it checks the veto
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let msg = ibc_client.module_ibc_action(
+        prop.proposer_chain.to_string(),
+        this_module(&app)?,
+        &InterchainGovIbcPacket::new(
+            load_state_hash(deps.storage)?,
+            InterchainGovIbcMsg::VetoProposal {
+                prop_hash: prop_id.clone(),
+                veto,
+            },
+        ),
+        None,
+    )?;
+    Ok(app
+        .response("forward_veto")
+        .add_message(msg)
+        .add_attribute("prop_id", prop_id))
+}
+
+/// Pause the governance of every member as a guardian of this chain
+fn pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    app: InterchainGov,
+    reason: String,
+) -> AdapterResult {
+    assert_not_paused(deps.storage, None)?;
+    let our_chain = ChainName::new(&env);
+    let guardian = load_guardian(deps.storage, &our_chain, info.sender.as_str())?;
+    let pause = Pause {
+        by: Authority::Guardian(guardian),
+        reason,
+    };
+    PAUSE.save(deps.storage, &pause)?;
+
+    // The pause isn't part of the committed state, the members apply it as it arrives
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let exec_msg = InterchainGovIbcPacket::new(
+        load_state_hash(deps.storage)?,
+        InterchainGovIbcMsg::Paused { pause },
+    );
+    let target_module = this_module(&app)?;
+    let msgs = MEMBERS_STATE_SYNC
+        .external_members(deps.storage, &env)?
+        .members
+        .iter()
+        .map(|host| {
+            ibc_client.module_ibc_action(host.to_string(), target_module.clone(), &exec_msg, None)
+        })
+        .collect::<AbstractSdkResult<Vec<_>>>()?;
+    Ok(app.response("pause").add_messages(msgs))
+}
+
+/// Store the outcome of a proposal, execute its action and report the outcome to the other members
+fn finish_prop(
+    mut deps: DepsMut,
+    env: Env,
+    app: InterchainGov,
+    prop_id: ProposalId,
+    prop: Proposal,
+    outcome: ProposalOutcome,
+) -> AdapterResult {
     // TODO: store each vote per chain
     FINALIZED_PROPOSALS.save(
         deps.storage,
         prop_id.clone(),
         &(prop.clone(), outcome.clone()),
     )?;
-    let external_members = MEMBERS_STATE_SYNC.external_members(deps.storage, &env)?;
+    let parent_hash = load_state_hash(deps.storage)?;
+    let outcome_hash = commit_state(
+        deps.storage,
+        &StateCommit::Outcome {
+            prop_id: prop_id.clone(),
+            outcome: outcome.clone(),
+        },
+    )?;
+    let deposit_msgs = settle_deposit(deps.storage, &prop_id, &prop, &outcome)?;
+    let mut external_members = MEMBERS_STATE_SYNC.external_members(deps.storage, &env)?;
 
     // Execute the prop
-    let new_member_msgs = match prop.action {
-        ProposalAction::UpdateMembers { mut members } => {
-            // If new members exclude self, update members to only be self
-            if !members.members.contains(&ChainName::new(&env)) {
-                MEMBERS_STATE_SYNC.save_members(deps.storage, &Members::new(&env))?;
-            }
-            let old_members = MEMBERS_STATE_SYNC.load_members(deps.storage)?;
-            MEMBERS_STATE_SYNC.save_members(deps.storage, &members)?;
-            let ibc_client = app.ibc_client(deps.as_ref());
-            let exec_msg = InterchainGovIbcMsg::JoinGov {
-                members: members.clone(),
-            };
-
-            // if new members, send them an inclusion msg
-            members.members.retain(|c| !old_members.members.contains(c));
-            let new_members = members;
-            // send inclusion messages to these chains
+    let action = prop.action_for(&outcome);
+    let mut action_packets = vec![];
+    let action_msgs = match action.clone() {
+        ProposalAction::UpdateMembers { members } => {
+            if outcome.passed {
+                // The members only change once every invitee responded
+                let invitees = invite_members(deps.storage, &env, members.clone())?;
+                // Invitees commit the resulting members on top of our outcome
+                let exec_msg = InterchainGovIbcPacket::new(
+                    outcome_hash,
+                    InterchainGovIbcMsg::JoinGov {
+                        members,
+                        invitees: invitees.clone(),
+                    },
+                );
 
-            let mut msgs = vec![];
-            let target_module = this_module(&app)?;
-            for host in new_members.members.iter() {
-                let callback = CallbackInfo::new(
-                    PROPOSE_CALLBACK_ID,
-                    Some(to_json_binary(&InterchainGovIbcCallbackMsg::JoinGov {
+                // send inclusion messages to the invitees
+                action_packets.extend(invitees.into_iter().map(|host| OutgoingPacket {
+                    callback: Some(InterchainGovIbcCallbackMsg::JoinGov {
                         proposed_to: host.clone(),
-                    })?),
-                );
-                msgs.push(ibc_client.module_ibc_action(
-                    host.to_string(),
-                    target_module.clone(),
-                    &exec_msg,
-                    Some(callback.clone()),
-                )?);
+                    }),
+                    to: host,
+                    packet: exec_msg.clone(),
+                }));
+            }
+            vec![]
+        }
+        ProposalAction::RemoveMember { member } => {
+            if outcome.passed {
+                remove_member(deps.storage, &member)?;
+                // The removed member doesn't get the result, it gets notified of its removal instead
+                external_members.members.retain(|m| m != &member);
+
+                action_packets.push(OutgoingPacket {
+                    to: member,
+                    packet: InterchainGovIbcPacket::new(
+                        parent_hash.clone(),
+                        InterchainGovIbcMsg::RemovedFromGov {
+                            prop_hash: prop_id.clone(),
+                            outcome: outcome.clone(),
+                        },
+                    ),
+                    callback: None,
+                });
+            }
+            vec![]
+        }
+        ProposalAction::SpendFromTreasury {
+            chain,
+            recipient,
+            amount,
+        } => {
+            if outcome.passed {
+                spend_from_treasury(deps.storage, deps.api, &env, &chain, &recipient, &amount)?
+            } else {
+                vec![]
+            }
+        }
+        ProposalAction::IbcTransfer {
+            from_chain,
+            to_chain,
+            recipient,
+            amount,
+        } => {
+            if outcome.passed {
+                ibc_transfer(
+                    deps.branch(),
+                    &env,
+                    &app,
+                    &prop_id,
+                    &from_chain,
+                    &to_chain,
+                    &recipient,
+                    &amount,
+                )?
+            } else {
+                vec![]
+            }
+        }
+        ProposalAction::UpdateConfig { config } => {
+            if outcome.passed {
+                // Applied once every member got the result
+                if external_members.members.is_empty() {
+                    GOV_CONFIG_SYNC.finalize_item_state(deps.storage, Some(config))?;
+                } else {
+                    GOV_CONFIG_SYNC.initiate_item_state(
+                        deps.storage,
+                        config,
+                        external_members.members.clone(),
+                    )?;
+                }
+            }
+            vec![]
+        }
+        ProposalAction::Pause { .. } | ProposalAction::Unpause {} => {
+            if outcome.passed {
+                apply_pause_action(deps.storage, &prop_id, &action)?;
+            }
+            vec![]
+        }
+        ProposalAction::UpdateGuardians { guardians } => {
+            if outcome.passed {
+                update_guardians(deps.storage, guardians)?;
+            }
+            vec![]
+        }
+        ProposalAction::VetoProposal {
+            prop_id: target,
+            reason,
+        } => {
+            if outcome.passed {
+                let (packets, deposit_msgs) =
+                    veto_by_proposal(deps.storage, &env, &prop_id, target, reason)?;
+                action_packets.extend(packets);
+                deposit_msgs.into_iter().map(SubMsg::new).collect()
+            } else {
+                vec![]
             }
-            msgs
         }
         ProposalAction::Signal => vec![],
     };
 
-    // Send mgs to other members to report vote outcome
-
-    PROPOSAL_STATE_SYNC
-        .set_outstanding_finalization_acks(deps.storage, external_members.members.clone())?;
-
-    let ibc_client = app.ibc_client(deps.as_ref());
-    let exec_msg = InterchainGovIbcMsg::ProposalResult {
-        prop_hash: prop_id.clone(),
+    // Send mgs to other members to report vote outcome, along with the packets of the action
+    let mut packets = outcome_packets(
+        deps.storage,
+        parent_hash,
+        &prop_id,
         outcome,
-    };
-    let mut msgs = vec![];
-    let target_module = this_module(&app)?;
-    for host in external_members.members.iter() {
-        let callback = CallbackInfo::new(
-            PROPOSE_CALLBACK_ID,
-            Some(to_json_binary(
-                &InterchainGovIbcCallbackMsg::ProposalResult {
-                    proposed_to: host.clone(),
-                    prop_hash: prop_id.clone(),
-                },
-            )?),
-        );
-        msgs.push(ibc_client.module_ibc_action(
-            host.to_string(),
-            target_module.clone(),
-            &exec_msg,
-            Some(callback.clone()),
-        )?);
-    }
+        &external_members.members,
+    )?;
+    packets.extend(action_packets);
+    let msgs = send_packets(deps.as_ref(), &app, packets)?;
     Ok(app
         .response("propose_members")
         .add_messages(msgs)
-        .add_messages(new_member_msgs)
+        .add_submessages(action_msgs)
+        .add_messages(deposit_msgs)
         .add_attribute("prop_id", prop_id))
 }
 
+/// Packets with the outcome of a proposal for the other members, their acks complete the proposal
+pub(crate) fn outcome_packets(
+    storage: &mut dyn Storage,
+    parent_hash: Binary,
+    prop_id: &ProposalId,
+    outcome: ProposalOutcome,
+    external_members: &[ChainName],
+) -> AdapterResult<Vec<OutgoingPacket>> {
+    PROPOSAL_STATE_SYNC.set_outstanding_finalization_acks(
+        storage,
+        prop_id.clone(),
+        external_members.to_vec(),
+    )?;
+
+    let exec_msg = InterchainGovIbcPacket::new(
+        parent_hash,
+        InterchainGovIbcMsg::ProposalResult {
+            prop_hash: prop_id.clone(),
+            outcome,
+        },
+    );
+    Ok(external_members
+        .iter()
+        .map(|host| OutgoingPacket {
+            to: host.clone(),
+            packet: exec_msg.clone(),
+            callback: Some(InterchainGovIbcCallbackMsg::ProposalResult {
+                proposed_to: host.clone(),
+                prop_hash: prop_id.clone(),
+            }),
+        })
+        .collect())
+}
+
+/// Packet for the governance of another member, see [`send_packets`]
+pub(crate) struct OutgoingPacket {
+    pub to: ChainName,
+    pub packet: InterchainGovIbcPacket,
+    /// Called back with [`PROPOSE_CALLBACK_ID`] once the packet is executed
+    pub callback: Option<InterchainGovIbcCallbackMsg>,
+}
+
+/// Send the packets, the ones for the same chain are bundled in one [`InterchainGovIbcPacket::batch`].
+/// A chain then executes them in order and acks them at once, with the callbacks of all of them.
+pub(crate) fn send_packets(
+    deps: Deps,
+    app: &InterchainGov,
+    packets: Vec<OutgoingPacket>,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let mut bundles: Vec<(ChainName, Vec<OutgoingPacket>)> = vec![];
+    for packet in packets {
+        match bundles.iter_mut().find(|(to, _)| to == &packet.to) {
+            Some((_, bundle)) => bundle.push(packet),
+            None => bundles.push((packet.to.clone(), vec![packet])),
+        }
+    }
+
+    let target_module = this_module(app)?;
+    let ibc_client = app.ibc_client(deps);
+    bundles
+        .into_iter()
+        .map(|(to, mut bundle)| {
+            let (packet, callback) = if bundle.len() == 1 {
+                let OutgoingPacket {
+                    packet, callback, ..
+                } = bundle.remove(0);
+                (packet, callback)
+            } else {
+                // Each message is built on the state committed by the previous ones
+                let parent_hash = bundle[0].packet.parent_hash.clone();
+                let (msgs, callbacks): (Vec<_>, Vec<_>) = bundle
+                    .into_iter()
+                    .map(|outgoing| (outgoing.packet.msg, outgoing.callback))
+                    .unzip();
+                let callbacks: Vec<_> = callbacks.into_iter().flatten().collect();
+                (
+                    InterchainGovIbcPacket::batch(parent_hash, msgs),
+                    (!callbacks.is_empty())
+                        .then_some(InterchainGovIbcCallbackMsg::Batch { callbacks }),
+                )
+            };
+            let callback = match callback {
+                Some(msg) => Some(CallbackInfo::new(
+                    PROPOSE_CALLBACK_ID,
+                    Some(to_json_binary(&msg)?),
+                )),
+                None => None,
+            };
+            Ok(ibc_client.module_ibc_action(
+                to.to_string(),
+                target_module.clone(),
+                &packet,
+                callback,
+            )?)
+        })
+        .collect()
+}
+
+/// Whether the votes reach the threshold, members that didn't vote count as abstaining
+fn threshold_passed(
+    threshold: &Threshold,
+    votes_for: u32,
+    votes_against: u32,
+    no_votes: u32,
+) -> bool {
+    let yes = Uint128::from(votes_for);
+    let members = Uint128::from(votes_for + votes_against);
+    let voted = members - Uint128::from(no_votes);
+    match threshold.clone() {
+        Threshold::AbsolutePercentage { percentage } => {
+            does_vote_count_pass(yes, members, percentage)
+        }
+        Threshold::ThresholdQuorum { threshold, quorum } => {
+            does_vote_count_pass(voted, members, quorum)
+                && does_vote_count_pass(yes, voted, threshold)
+        }
+        Threshold::AbsoluteCount { threshold } => yes >= threshold,
+    }
+}
+
 /// Reach out to external members and request their local vote results
 fn request_vote_results(
     deps: DepsMut,
@@ -230,8 +628,9 @@ fn request_vote_results(
 ) -> AdapterResult {
     let (prop, _state) = load_proposal(deps.storage, &prop_id)?;
 
-    // We can only tally upon expiration
-    if !prop.expiration.is_expired(&env.block) {
+    // We can only tally upon expiration, emergency proposals as soon as they pass
+    let open = !prop.expiration.is_expired(&env.block);
+    if open && !prop.emergency {
         return Err(InterchainGovError::ProposalStillOpen(prop_id.clone()));
     }
 
@@ -244,29 +643,38 @@ fn request_vote_results(
     let existing_vote_results = VOTE_RESULTS
         .prefix(prop_id.clone())
         .range(deps.storage, None, None, Order::Ascending)
-        .collect::<StdResult<Vec<(ChainName, Option<GovernanceVote>)>>>()?;
+        .collect::<StdResult<Vec<(ChainName, VoteResult)>>>()?;
 
     // If we don't have any vote results, we need to query them
     if !existing_vote_results.is_empty() {
         // if we have pending votes, check they're all resolved
-        return if existing_vote_results.iter().any(|(_, vote)| vote.is_none()) {
-            Err(InterchainGovError::VotesStillPending {
+        if existing_vote_results
+            .iter()
+            .any(|(_, vote)| vote.is_pending())
+        {
+            return Err(InterchainGovError::VotesStillPending {
                 prop_id: prop_id.clone(),
                 chains: existing_vote_results
                     .into_iter()
                     .map(|(chain, _)| chain.clone())
                     .collect(),
-            })
-        } else {
+            });
+        }
+        if !open {
             // happy path error
-            Err(InterchainGovError::VotesAlreadyFinalized(prop_id.clone()))
-        };
+            return Err(InterchainGovError::VotesAlreadyFinalized(prop_id.clone()));
+        }
+        // Open emergency proposals that didn't pass yet get their votes queried again
+        for (chain, _) in existing_vote_results {
+            VOTE_RESULTS.remove(deps.storage, (prop_id.clone(), &chain));
+        }
     };
 
-    // Ask everyone to give us their votes
+    // Ask the members of the proposal's snapshot that are still around to give us their votes
     let external_members = load_external_members(deps.storage, &env)?;
     let vote_queries = external_members
         .iter()
+        .filter(|host| prop.members.members.contains(host))
         .map(|host| -> AbstractSdkResult<CosmosMsg> {
             let ibc_client = app.ibc_client(deps.as_ref());
             let module_addr = TEMP_REMOTE_GOV_MODULE_ADDRS.load(deps.storage, host)?;
@@ -284,7 +692,7 @@ fn request_vote_results(
             )?;
 
             // Mark the vote result as pending
-            VOTE_RESULTS.save(deps.storage, (prop_id.clone(), host), &None)?;
+            VOTE_RESULTS.save(deps.storage, (prop_id.clone(), host), &VoteResult::Pending)?;
 
             Ok(query)
         })
@@ -308,7 +716,7 @@ fn request_gov_vote_details(
     let existing_vote_results = VOTE_RESULTS
         .prefix(prop_id.clone())
         .range(deps.storage, None, None, Order::Ascending)
-        .collect::<StdResult<Vec<(ChainName, Option<GovernanceVote>)>>>()?;
+        .collect::<StdResult<Vec<(ChainName, VoteResult)>>>()?;
 
     // If we don't have any vote results, we need to query them
     if existing_vote_results.is_empty() {
@@ -317,7 +725,10 @@ fn request_gov_vote_details(
         });
     } else {
         // if we have pending votes, check they're all resolved
-        if existing_vote_results.iter().any(|(_, vote)| vote.is_none()) {
+        if existing_vote_results
+            .iter()
+            .any(|(_, vote)| vote.is_pending())
+        {
             return Err(InterchainGovError::VotesStillPending {
                 prop_id: prop_id.clone(),
                 chains: existing_vote_results
@@ -410,13 +821,18 @@ fn do_vote(
     vote: Vote,
     governance: Governance,
 ) -> AdapterResult {
-    println!("Voting on proposal: {:?} with vote: {:?}", prop_id, vote);
     PROPOSAL_STATE_SYNC.assert_finalized(deps.storage, prop_id.clone())?;
 
     let (prop, _) = PROPOSAL_STATE_SYNC.load(deps.storage, prop_id.clone())?;
     if prop.expiration.is_expired(&env.block) {
         return Err(InterchainGovError::ProposalExpired(prop_id.clone()));
     }
+    // Multiple choice proposals take an option, the others yes or no
+    match (&prop.choices, &vote) {
+        (_, Vote::NoVote) | (None, Vote::Yes | Vote::No) => {}
+        (Some(choices), Vote::Choice(index)) if (*index as usize) < choices.options.len() => {}
+        _ => return Err(InterchainGovError::InvalidVote(prop_id)),
+    }
 
     /*
 
@@ -432,38 +848,141 @@ fn do_vote(
         prop_id.clone(),
         &GovernanceVote::new(governance, vote.clone()),
     )?;
-    PROPOSAL_STATE_SYNC.finalize_kv_state(deps.storage, prop_id.clone(), Some((prop, vote)))?;
+    let event =
+        PROPOSAL_STATE_SYNC.finalize_kv_state(deps.storage, prop_id.clone(), Some((prop, vote)))?;
 
     Ok(app
         .response("vote_proposal")
-        .add_attribute("prop_id", prop_id))
+        .add_attribute("prop_id", prop_id)
+        .add_attributes(event.to_attributes()))
 }
 
 fn test_add_members(deps: DepsMut, app: InterchainGov, members: Members) -> AdapterResult {
-    MEMBERS.update(deps.storage, |mut m| -> StdResult<Members> {
-        m = members;
-        Ok(m)
-    })?;
+    MEMBERS_STATE_SYNC.save_members(deps.storage, &members)?;
 
     Ok(app.response("update_members"))
 }
 
+/// Check that the action can be executed by the members of the proposal
+fn validate_action(
+    deps: Deps,
+    env: &Env,
+    config: &GovConfig,
+    prop: &Proposal,
+    external_members: &Members,
+    action: &ProposalAction,
+) -> AdapterResult<()> {
+    config.assert_action_allowed(action)?;
+
+    // Only existing members can be removed
+    if let ProposalAction::RemoveMember { member } = action {
+        if !external_members.members.contains(member) {
+            return Err(InterchainGovError::UnknownMember {
+                member: member.clone(),
+            });
+        }
+    }
+    if let ProposalAction::SpendFromTreasury {
+        chain,
+        recipient,
+        amount,
+    } = action
+    {
+        if !prop.members.members.contains(chain) {
+            return Err(InterchainGovError::UnknownMember {
+                member: chain.clone(),
+            });
+        }
+        // Recipients on other chains can't be validated here
+        if chain == &ChainName::new(env) {
+            deps.api.addr_validate(recipient)?;
+        }
+        assert_treasury_funds(deps.storage, chain, amount)?;
+    }
+    if let ProposalAction::UpdateMembers { members } = action {
+        if let Some(max) = config.max_members {
+            if members.members.len() > max as usize {
+                return Err(InterchainGovError::TooManyMembers { max });
+            }
+        }
+    }
+    if let ProposalAction::UpdateConfig { config } = action {
+        config.validate(deps.api)?;
+    }
+    if let ProposalAction::UpdateGuardians { guardians } = action {
+        if let Some(guardian) = guardians
+            .members
+            .iter()
+            .find(|g| !prop.members.members.contains(&g.chain))
+        {
+            return Err(InterchainGovError::UnknownMember {
+                member: guardian.chain.clone(),
+            });
+        }
+    }
+    if let ProposalAction::VetoProposal { prop_id, .. } = action {
+        if !PROPOSAL_STATE_SYNC.has(deps.storage, prop_id.clone()) {
+            return Err(InterchainGovError::ProposalNotFound(prop_id.clone()));
+        }
+    }
+    if let ProposalAction::IbcTransfer {
+        from_chain,
+        to_chain,
+        amount,
+        ..
+    } = action
+    {
+        if !prop.members.members.contains(from_chain) {
+            return Err(InterchainGovError::UnknownMember {
+                member: from_chain.clone(),
+            });
+        }
+        if from_chain == to_chain {
+            return Err(InterchainGovError::SameChainTransfer {
+                chain: from_chain.clone(),
+            });
+        }
+        assert_transfer_callbacks(from_chain)?;
+        assert_treasury_funds(deps.storage, from_chain, amount)?;
+    }
+    Ok(())
+}
+
 /// Propose a new message to the interchain DAO
-/// 1. Create a new proposal
+/// 1. Create a new proposal, taking the deposit from the account if one is required
 /// 2. Vote on our own proposal
 /// 3. Propose to other chains, adding a callback to update the proposal's proposed state (to ensure they received it)
 ///
 fn propose(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     app: InterchainGov,
     proposal: ProposalMsg,
 ) -> AdapterResult {
     // 1.
-    let hash = <sha2::Sha256 as sha2::Digest>::digest(proposal.to_string());
-    let prop_id = base64::prelude::BASE64_STANDARD.encode(hash.as_slice());
-    let prop = Proposal::new(proposal.clone(), &info.sender, &env);
+    let prop_id = proposal.hash();
+    let config = load_gov_config(deps.storage)?;
+    let prop = Proposal::new(
+        proposal.clone(),
+        &info.sender,
+        &env,
+        MEMBERS_STATE_SYNC.load_epoch(deps.storage)?,
+        MEMBERS_STATE_SYNC.load_members(deps.storage)?,
+        &config,
+    );
+    assert_not_paused(deps.storage, Some(&prop))?;
+    config.assert_voting_period(&prop, &env.block)?;
+    if prop.emergency {
+        if config.emergency_threshold.is_none() {
+            return Err(InterchainGovError::EmergencyDisabled);
+        }
+        if !prop.action.allows_emergency() {
+            return Err(InterchainGovError::EmergencyNotAllowed {
+                action: prop.action.name().to_string(),
+            });
+        }
+    }
 
     // check that prop doesn't exist
     if PROPOSAL_STATE_SYNC.has(deps.storage, prop_id.clone()) {
@@ -472,29 +991,59 @@ fn propose(
 
     let external_members = MEMBERS_STATE_SYNC.external_members(deps.storage, &env)?;
 
+    if let Some(choices) = &prop.choices {
+        if prop.action != ProposalAction::Signal || choices.options.len() < 2 {
+            return Err(InterchainGovError::InvalidChoices(
+                "at least two options are needed, their actions replace the proposal's".to_string(),
+            ));
+        }
+    }
+    for action in prop.actions() {
+        validate_action(
+            deps.as_ref(),
+            &env,
+            &config,
+            &prop,
+            &external_members,
+            action,
+        )?;
+    }
+
+    let deposit_msgs = take_deposit(deps.branch(), &env, &app, &prop_id)?;
+
     if external_members.members.is_empty() {
-        // We have initiate state for the proposal, it will be proposed when we send it to other chains and get all confirmations
-        PROPOSAL_STATE_SYNC.finalize_kv_state(
+        // Without other members the proposal is committed right away
+        let step = PROPOSAL_TWO_PHASE.prepare(
             deps.storage,
             prop_id.clone(),
-            Some((prop.clone(), Vote::Yes)),
+            (prop.clone(), Vote::Yes),
+            vec![],
+        )?;
+        let prop_hash = commit_state(
+            deps.storage,
+            &StateCommit::Proposal {
+                prop_id: prop_id.clone(),
+                prop: Box::new(prop.clone()),
+            },
         )?;
         match prop.action {
             // If goal is to update members, send an IBC packet to members to update their state
-            ProposalAction::UpdateMembers { mut members } => {
-                MEMBERS_STATE_SYNC.initiate_members(deps.storage, &env, members.clone())?;
-                // send msgs to new members
+            ProposalAction::UpdateMembers { members } => {
+                let invitees = invite_members(deps.storage, &env, members.clone())?;
+                // send msgs to new members, they commit the members on top of our state once
+                // every invitee responded
                 let ibc_client = app.ibc_client(deps.as_ref());
-                let exec_msg = InterchainGovIbcMsg::JoinGov {
-                    members: members.clone(),
-                };
-
-                // filter out self
-                members.members.retain(|c| c != &ChainName::new(&env));
+                let exec_msg = InterchainGovIbcPacket::new(
+                    prop_hash,
+                    InterchainGovIbcMsg::JoinGov {
+                        members,
+                        invitees: invitees.clone(),
+                    },
+                );
 
                 let mut msgs = vec![];
                 let target_module = this_module(&app)?;
-                for host in members.members.iter() {
+                for host in invitees.iter() {
                     let callback = CallbackInfo::new(
                         PROPOSE_CALLBACK_ID,
                         Some(to_json_binary(&InterchainGovIbcCallbackMsg::JoinGov {
@@ -511,63 +1060,40 @@ fn propose(
 
                 return Ok(app
                     .response("propose_members")
+                    .add_messages(deposit_msgs)
                     .add_messages(msgs)
-                    .add_attribute("prop_id", prop_id));
+                    .add_attribute("prop_id", prop_id)
+                    .add_attribute("invitees", invitees.len().to_string())
+                    .add_attributes(step.events.iter().flat_map(SyncEvent::to_attributes)));
             }
-            ProposalAction::Signal => {
+            _ => {
                 return Ok(app
                     .response("propose_and_accept")
-                    .add_attribute("prop_id", prop_id))
+                    .add_messages(deposit_msgs)
+                    .add_attribute("prop_id", prop_id)
+                    .add_attributes(step.events.iter().flat_map(SyncEvent::to_attributes)))
             }
         }
     };
 
     // Here we have to account for all other instances of the app.
 
-    // 2.
-    // We have initiate state for the proposal, it will be proposed when we send it to other chains and get all confirmations
-    PROPOSAL_STATE_SYNC.initiate_kv_state(
+    // 2. + 3.
+    // We have initiate state for the proposal, it will be finalized once all other members prepared it
+    let parent_hash = load_state_hash(deps.storage)?;
+    let step = PROPOSAL_TWO_PHASE.prepare(
         deps.storage,
         prop_id.clone(),
-        (prop.clone(), Vote::Yes),
-        external_members.members.clone(),
+        (prop, Vote::Yes),
+        external_members.members,
     )?;
-
-    // 3.
-    let target_module = this_module(&app)?;
-
-    // Loop through members and propose to them the proposal (TODO: do we actually need ourselves stored)?
-    let propose_msgs = external_members
-        .members
-        .iter()
-        .map(|host| {
-            let callback = CallbackInfo::new(
-                PROPOSE_CALLBACK_ID,
-                Some(to_json_binary(
-                    &InterchainGovIbcCallbackMsg::ProposeProposal {
-                        prop_hash: prop_id.clone(),
-                        proposed_to: host.clone(),
-                    },
-                )?),
-            );
-            let ibc_client = app.ibc_client(deps.as_ref());
-            let msg = ibc_client.module_ibc_action(
-                host.to_string(),
-                target_module.clone(),
-                &InterchainGovIbcMsg::ProposeProposal {
-                    prop: prop.clone(),
-                    prop_hash: prop_id.clone(),
-                    chain: host.clone(),
-                },
-                Some(callback.clone()),
-            )?;
-            Ok(msg)
-        })
-        .collect::<AbstractSdkResult<Vec<CosmosMsg>>>()?;
+    let propose_msgs = proposal_packet_msgs(deps.as_ref(), &app, parent_hash, step.packets)?;
 
     Ok(app
         .response("propose")
         .add_attribute("prop_id", prop_id)
+        .add_attributes(step.events.iter().flat_map(SyncEvent::to_attributes))
+        .add_messages(deposit_msgs)
         .add_messages(propose_msgs))
 }
 
@@ -584,55 +1110,323 @@ fn load_external_members(storage: &mut dyn Storage, env: &Env) -> AdapterResult<
 /// Send finalization message over IBC
 pub fn finalize(
     deps: DepsMut,
-    env: Env,
+    _env: Env,
     _info: MessageInfo,
     app: InterchainGov,
     prop_id: ProposalId,
 ) -> AdapterResult {
-    // State is already finalized after receiving the final proposal.
+    // Errors unless every member prepared the proposal
+    let packets = PROPOSAL_TWO_PHASE.commit(deps.storage, prop_id.clone())?;
+    let (prop, _) = load_proposal(deps.storage, &prop_id)?;
+
+    // Members commit the proposal once they finalize it
+    let parent_hash = load_state_hash(deps.storage)?;
+    commit_state(
+        deps.storage,
+        &StateCommit::Proposal {
+            prop_id: prop_id.clone(),
+            prop: Box::new(prop),
+        },
+    )?;
+    let finalize_messages = proposal_packet_msgs(deps.as_ref(), &app, parent_hash, packets)?;
 
+    Ok(app
+        .response("finalize")
+        .add_attribute("prop_id", prop_id)
+        .add_messages(finalize_messages))
+}
+
+/// Send the two-phase commit packets of a proposal to the members
+pub(crate) fn proposal_packet_msgs(
+    deps: Deps,
+    app: &InterchainGov,
+    parent_hash: Binary,
+    packets: Vec<Packet<ProposalId, (Proposal, Vote)>>,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let target_module = this_module(app)?;
+    let ibc_client = app.ibc_client(deps);
+    packets
+        .into_iter()
+        .map(|Packet { to, msg }| {
+            let (callback_id, callback_msg, ibc_msg) = match msg {
+                TwoPhaseMsg::Prepare {
+                    key,
+                    value: (prop, _),
+                } => (
+                    PROPOSE_CALLBACK_ID,
+                    InterchainGovIbcCallbackMsg::ProposeProposal {
+                        prop_hash: key.clone(),
+                        proposed_to: to.clone(),
+                    },
+                    InterchainGovIbcMsg::ProposeProposal {
+                        prop_hash: key,
+                        prop,
+                        chain: to.clone(),
+                    },
+                ),
+                TwoPhaseMsg::Commit { key } => (
+                    FINALIZE_CALLBACK_ID,
+                    InterchainGovIbcCallbackMsg::FinalizeProposal {
+                        prop_hash: key.clone(),
+                        proposed_to: to.clone(),
+                    },
+                    InterchainGovIbcMsg::FinalizeProposal { prop_hash: key },
+                ),
+                TwoPhaseMsg::Abort { key } => (
+                    FINALIZE_CALLBACK_ID,
+                    InterchainGovIbcCallbackMsg::AbortProposal {
+                        prop_hash: key.clone(),
+                        proposed_to: to.clone(),
+                    },
+                    InterchainGovIbcMsg::AbortProposal { prop_hash: key },
+                ),
+            };
+            let callback = CallbackInfo::new(callback_id, Some(to_json_binary(&callback_msg)?));
+            let msg = ibc_client.module_ibc_action(
+                to.to_string(),
+                target_module.clone(),
+                &InterchainGovIbcPacket::new(parent_hash.clone(), ibc_msg),
+                Some(callback),
+            )?;
+            Ok(msg)
+        })
+        .collect()
+}
+
+/// Answer a pending invite to join a governance
+/// 1. Join the governance if the invite is accepted
+/// 2. Notify all the members of the governance of our answer
+fn respond_to_invite(
+    deps: DepsMut,
+    env: Env,
+    app: InterchainGov,
+    inviter: ChainName,
+    accept: bool,
+) -> AdapterResult {
+    let invite = PENDING_INVITES
+        .may_load(deps.storage, &inviter)?
+        .ok_or_else(|| InterchainGovError::InviteNotFound {
+            inviter: inviter.clone(),
+        })?;
+    PENDING_INVITES.remove(deps.storage, &inviter);
+    let mut members = invite.members.clone();
+
+    // 1.
+    let mut events = vec![];
+    if accept {
+        MEMBERS_STATE_SYNC.assert_finalized(deps.storage)?;
+        // Continue from the governance's state
+        STATE_HASH.save(deps.storage, &invite.parent_hash)?;
+        events = join_members(deps.storage, &env, invite)?;
+    }
+    let parent_hash = load_state_hash(deps.storage)?;
+
+    // 2.
+    members.members.retain(|c| c != &ChainName::new(&env));
     let target_module = this_module(&app)?;
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let response_msgs = members
+        .members
+        .iter()
+        .map(|host| {
+            ibc_client.module_ibc_action(
+                host.to_string(),
+                target_module.clone(),
+                &InterchainGovIbcPacket::new(
+                    parent_hash.clone(),
+                    InterchainGovIbcMsg::InviteResponse { accepted: accept },
+                ),
+                None,
+            )
+        })
+        .collect::<AbstractSdkResult<Vec<CosmosMsg>>>()?;
+
+    Ok(app
+        .response(if accept {
+            "accept_invite"
+        } else {
+            "reject_invite"
+        })
+        .add_attribute("inviter", inviter.to_string())
+        .add_attributes(events.iter().flat_map(SyncEvent::to_attributes))
+        .add_messages(response_msgs))
+}
+
+/// Query the state of every external member to compare it with ours
+/// 1. Reset the report of each member
+/// 2. Query their members and proposals, the diff is computed in the callback
+fn sync_check(deps: DepsMut, env: Env, app: InterchainGov) -> AdapterResult {
+    let external_members = load_external_members(deps.storage, &env)?;
+    if external_members.is_empty() {
+        return Err(InterchainGovError::NoGovernance);
+    }
+
+    // 1.
+    SYNC_REPORTS.clear(deps.storage);
+    for host in external_members.iter() {
+        SYNC_REPORTS.save(deps.storage, host, &SyncReport::new(env.block.height))?;
+    }
+
+    // 2.
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let mut queries = vec![];
+    for host in external_members.iter() {
+        let module_addr = TEMP_REMOTE_GOV_MODULE_ADDRS.load(deps.storage, host)?;
+        let callback = CallbackInfo::new(
+            SYNC_CHECK_ID,
+            Some(to_json_binary(&InterchainGovIbcCallbackMsg::SyncCheck {
+                chain: host.clone(),
+            })?),
+        );
+        for query in [
+            InterchainGovQueryMsg::Members {},
+            InterchainGovQueryMsg::ProposalHashes {},
+        ] {
+            queries.push(ibc_client.ibc_query(
+                host.to_string(),
+                WasmQuery::Smart {
+                    contract_addr: module_addr.clone(),
+                    msg: to_json_binary(&crate::msg::QueryMsg::Module(query))?,
+                },
+                callback.clone(),
+            )?);
+        }
+    }
+
+    Ok(app.response("sync_check").add_messages(queries))
+}
+
+/// Leave the interchain governance
+/// 1. Drop all proposals that didn't get an outcome yet, together with their votes
+/// 2. Reset the members to only ourselves
+/// 3. Notify the other members that we left
+fn leave_governance(deps: DepsMut, env: Env, app: InterchainGov) -> AdapterResult {
+    MEMBERS_STATE_SYNC.assert_finalized(deps.storage)?;
 
     let external_members = MEMBERS_STATE_SYNC.external_members(deps.storage, &env)?;
+    if external_members.members.is_empty() {
+        return Err(InterchainGovError::NoGovernance);
+    }
 
-    // set outstanding acks
-    PROPOSAL_STATE_SYNC
-        .set_outstanding_finalization_acks(deps.storage, external_members.members.clone())?;
+    // 1. & 2.
+    let parent_hash = load_state_hash(deps.storage)?;
+    let refunds = reset_membership(deps.storage, &env)?;
 
+    // 3.
+    let target_module = this_module(&app)?;
     let ibc_client = app.ibc_client(deps.as_ref());
-    let finalize_messages = external_members
+    let leave_msgs = external_members
         .members
         .iter()
         .map(|host| {
-            let callback = CallbackInfo::new(
-                FINALIZE_CALLBACK_ID,
-                Some(to_json_binary(
-                    &InterchainGovIbcCallbackMsg::FinalizeProposal {
-                        prop_hash: prop_id.clone(),
-                        proposed_to: host.clone(),
-                    },
-                )?),
-            );
-            let msg = ibc_client.module_ibc_action(
+            ibc_client.module_ibc_action(
                 host.to_string(),
                 target_module.clone(),
-                &InterchainGovIbcMsg::FinalizeProposal {
-                    prop_hash: prop_id.clone(),
-                },
-                Some(callback.clone()),
-            )?;
+                &InterchainGovIbcPacket::new(parent_hash.clone(), InterchainGovIbcMsg::LeaveGov {}),
+                None,
+            )
+        })
+        .collect::<AbstractSdkResult<Vec<CosmosMsg>>>()?;
 
-            Ok(msg)
+    Ok(app
+        .response("leave_governance")
+        .add_messages(leave_msgs)
+        .add_messages(refunds))
+}
+
+/// Pledge funds of the account to the treasury and notify the other members
+fn deposit_to_treasury(
+    deps: DepsMut,
+    env: Env,
+    app: InterchainGov,
+    funds: Vec<Coin>,
+) -> AdapterResult {
+    // Funds of a chain are all held by the governing account
+    let proxy = app.proxy_address(deps.as_ref())?;
+    if proxy != governing_account(deps.storage)? {
+        return Err(InterchainGovError::Unauthorized {});
+    }
+
+    let our_chain = ChainName::new(&env);
+    record_deposit(deps.storage, &our_chain, &funds)?;
+    // The account has to hold everything that was pledged
+    for coin in &funds {
+        let pledged = treasury_balance(deps.storage, &our_chain, &coin.denom)?;
+        if deps.querier.query_balance(&proxy, &coin.denom)?.amount < pledged.amount {
+            return Err(InterchainGovError::InsufficientTreasury {
+                chain: our_chain,
+                amount: pledged,
+            });
+        }
+    }
+
+    let external_members = MEMBERS_STATE_SYNC.external_members(deps.storage, &env)?;
+    let parent_hash = load_state_hash(deps.storage)?;
+    let target_module = this_module(&app)?;
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let deposit_msgs = external_members
+        .members
+        .iter()
+        .map(|host| {
+            ibc_client.module_ibc_action(
+                host.to_string(),
+                target_module.clone(),
+                &InterchainGovIbcPacket::new(
+                    parent_hash.clone(),
+                    InterchainGovIbcMsg::TreasuryDeposit {
+                        funds: funds.clone(),
+                    },
+                ),
+                None,
+            )
         })
         .collect::<AbstractSdkResult<Vec<CosmosMsg>>>()?;
 
     Ok(app
-        .response("finalize")
-        .add_attribute("prop_id", prop_id)
-        .add_messages(finalize_messages))
+        .response("deposit_to_treasury")
+        .add_messages(deposit_msgs))
+}
+
+/// Delegate the vote of this chain to another member, or drop the delegation if None
+fn delegate_vote(
+    deps: DepsMut,
+    env: Env,
+    app: InterchainGov,
+    delegation: Option<Delegation>,
+) -> AdapterResult {
+    let our_chain = ChainName::new(&env);
+    let external_members = MEMBERS_STATE_SYNC.external_members(deps.storage, &env)?;
+    match &delegation {
+        Some(delegation) => {
+            if !external_members.members.contains(&delegation.delegate) {
+                return Err(InterchainGovError::UnknownMember {
+                    member: delegation.delegate.clone(),
+                });
+            }
+            DELEGATIONS.save(deps.storage, &our_chain, delegation)?;
+        }
+        None => DELEGATIONS.remove(deps.storage, &our_chain),
+    }
+
+    // The delegations aren't part of the committed state, no parent hash to check
+    let exec_msg = InterchainGovIbcPacket::new(
+        load_state_hash(deps.storage)?,
+        InterchainGovIbcMsg::DelegationChanged { delegation },
+    );
+    let target_module = this_module(&app)?;
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let msgs = external_members
+        .members
+        .iter()
+        .map(|host| {
+            ibc_client.module_ibc_action(host.to_string(), target_module.clone(), &exec_msg, None)
+        })
+        .collect::<AbstractSdkResult<Vec<CosmosMsg>>>()?;
+
+    Ok(app.response("delegate_vote").add_messages(msgs))
 }
 
-fn this_module(app: &InterchainGov) -> AbstractResult<ModuleInfo> {
+pub(crate) fn this_module(app: &InterchainGov) -> AbstractResult<ModuleInfo> {
     ModuleInfo::from_id(app.module_id(), app.version().into())
 }
 
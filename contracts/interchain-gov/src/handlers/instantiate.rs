@@ -1,9 +1,11 @@
 use crate::{
     contract::{AdapterResult, InterchainGov},
     msg::InterchainGovInstantiateMsg,
-    state::{Members, ALLOW_JOINING_GOV, MEMBERS},
+    state::{
+        GovConfig, Members, ALLOW_JOINING_GOV, GOVERNING_ACCOUNT, GOV_CONFIG, MEMBERS_STATE_SYNC,
+    },
 };
-use abstract_adapter::sdk::AbstractResponse;
+use abstract_adapter::sdk::{AbstractResponse, AccountVerification};
 
 use cosmwasm_std::{DepsMut, Env, MessageInfo};
 
@@ -14,8 +16,23 @@ pub fn instantiate_handler(
     app: InterchainGov,
     msg: InterchainGovInstantiateMsg,
 ) -> AdapterResult {
-    MEMBERS.save(deps.storage, &Members::new(&env))?;
+    let account = deps.api.addr_validate(&msg.account)?;
+    app.account_registry(deps.as_ref())?
+        .assert_proxy(&account)?;
+    GOVERNING_ACCOUNT.save(deps.storage, &account)?;
+    MEMBERS_STATE_SYNC.save_members(deps.storage, &Members::new(&env))?;
     ALLOW_JOINING_GOV.save(deps.storage, &msg.accept_proposal_from_gov)?;
+    let deposit = msg
+        .deposit
+        .map(|deposit| deposit.check(deps.api))
+        .transpose()?;
+    GOV_CONFIG.save(
+        deps.storage,
+        &GovConfig {
+            deposit,
+            ..Default::default()
+        },
+    )?;
 
     Ok(app.response("instantiate"))
 }
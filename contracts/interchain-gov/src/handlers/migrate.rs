@@ -0,0 +1,154 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use abstract_adapter::objects::module_version::{assert_contract_upgrade, set_module_data};
+use abstract_adapter::sdk::AbstractResponse;
+use cosmwasm_std::{DepsMut, Env, Order, StdResult, Storage};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Item, Map};
+use ibc_sync_state::DataState;
+
+use crate::contract::{AdapterResult, InterchainGov};
+use crate::dependencies::IBC_CLIENT_DEP;
+use crate::msg::InterchainGovMigrateMsg;
+use crate::state::{
+    GovernanceVote, Proposal, ProposalId, VoteResult, FINALIZED_PROPOSALS, GOVERNING_ACCOUNT,
+    GOV_CONFIG_SYNC, MEMBERSHIP_EPOCH, MEMBERS_AT, MEMBERS_STATE_SYNC, PENDING_QUERIES,
+    PROPOSAL_STATE_SYNC, VOTE_RESULTS,
+};
+use crate::upgrade::parse_version;
+use crate::{InterchainGovError, ADAPTER_VERSION, MY_ADAPTER_ID};
+
+type Migration = fn(DepsMut, &InterchainGovMigrateMsg) -> AdapterResult<()>;
+
+/// State migrations by the version that introduced them, oldest first.
+/// Contracts stored at an older version run every migration after it.
+const MIGRATIONS: &[(&str, Migration)] = &[("0.1.0", v0_1_0)];
+
+pub fn migrate_handler(
+    mut deps: DepsMut,
+    _env: Env,
+    app: InterchainGov,
+    msg: InterchainGovMigrateMsg,
+) -> AdapterResult {
+    let from = get_contract_version(deps.storage)?.version;
+    let from_version = parse_version(&from)?;
+    assert_contract_upgrade(deps.storage, MY_ADAPTER_ID, parse_version(ADAPTER_VERSION)?)?;
+    assert_not_syncing(deps.storage)?;
+
+    for (version, migration) in MIGRATIONS {
+        if from_version < parse_version(version)? {
+            migration(deps.branch(), &msg)?;
+        }
+    }
+
+    set_module_data(
+        deps.storage,
+        MY_ADAPTER_ID,
+        ADAPTER_VERSION,
+        &[IBC_CLIENT_DEP],
+        None::<String>,
+    )?;
+    set_contract_version(deps.storage, MY_ADAPTER_ID, ADAPTER_VERSION)?;
+
+    Ok(app
+        .response("migrate")
+        .add_attribute("from", from)
+        .add_attribute("to", ADAPTER_VERSION))
+}
+
+/// Synced values are stored in the layout of the version that proposed them, so the state can't
+/// be migrated until they are finalized or reverted
+fn assert_not_syncing(storage: &dyn Storage) -> AdapterResult<()> {
+    let syncing = PROPOSAL_STATE_SYNC
+        .state_status_map()
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        // Backups are stored next to the states
+        .filter(|(_, _, state)| DataState::from_num(*state).is_some())
+        .count();
+    if syncing > 0 {
+        return Err(InterchainGovError::MigrateWhileSyncing {
+            what: format!("{syncing} proposals"),
+        });
+    }
+    if MEMBERS_STATE_SYNC.assert_finalized(storage).is_err() {
+        return Err(InterchainGovError::MigrateWhileSyncing {
+            what: "members".to_string(),
+        });
+    }
+    if GOV_CONFIG_SYNC.data_state(storage).is_some() {
+        return Err(InterchainGovError::MigrateWhileSyncing {
+            what: "config".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// 0.1.0 tracks the members by epoch, stores the members that vote on each proposal and the
+/// governed account. Registered ICQs move out of the namespace they shared with the pending replies
+/// and vote results tell members that didn't vote apart from pending ones. Proposal acks are
+/// tracked per proposal.
+fn v0_1_0(deps: DepsMut, msg: &InterchainGovMigrateMsg) -> AdapterResult<()> {
+    let account = msg
+        .account
+        .as_ref()
+        .ok_or(InterchainGovError::NoGoverningAccount)?;
+    GOVERNING_ACCOUNT.save(deps.storage, &deps.api.addr_validate(account)?)?;
+
+    let storage = deps.storage;
+    // Outstanding acks of the members sync in 0.0.1, empty once the members are finalized
+    const MEMBERS_ACKS: Item<Vec<ChainName>> = Item::new("acks");
+    MEMBERS_ACKS.remove(storage);
+    // Acks of the proposal results were shared by every proposal in 0.0.1, now they are per proposal
+    const PROPOSALS_ACKS: Item<Vec<ChainName>> = Item::new(ibc_sync_state::MAP_ACKS_NAMESPACE);
+    PROPOSALS_ACKS.remove(storage);
+
+    // Replies are handled in the transaction that registers the query, only queries remain
+    const OLD_PENDING_QUERIES: Map<u64, (ChainName, ProposalId)> = Map::new("pending_replies");
+    let queries = OLD_PENDING_QUERIES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (query_id, pending) in queries {
+        OLD_PENDING_QUERIES.remove(storage, query_id);
+        PENDING_QUERIES.save(storage, query_id, &pending)?;
+    }
+
+    // Members that didn't vote had no result, only requested and cast votes were stored
+    const OLD_VOTE_RESULTS: Map<(ProposalId, &ChainName), Option<GovernanceVote>> =
+        Map::new("vote_results");
+    let results = OLD_VOTE_RESULTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((prop_id, chain), result) in results {
+        let result = match result {
+            Some(vote) => VoteResult::Cast(vote),
+            None => VoteResult::Pending,
+        };
+        VOTE_RESULTS.save(storage, (prop_id, &chain), &result)?;
+    }
+
+    // The current members voted on every proposal so far
+    let members = MEMBERS_STATE_SYNC.load_members(storage)?;
+    MEMBERSHIP_EPOCH.save(storage, &0)?;
+    MEMBERS_AT.save(storage, 0, &members)?;
+    let with_members = |prop: Proposal| Proposal {
+        epoch: 0,
+        members: members.clone(),
+        ..prop
+    };
+
+    let proposals = PROPOSAL_STATE_SYNC.map();
+    let stored = proposals
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (prop_id, (prop, vote)) in stored {
+        proposals.save(storage, prop_id, &(with_members(prop), vote))?;
+    }
+    let finalized = FINALIZED_PROPOSALS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (prop_id, (prop, outcome)) in finalized {
+        FINALIZED_PROPOSALS.save(storage, prop_id, &(with_members(prop), outcome))?;
+    }
+    Ok(())
+}
@@ -1,10 +1,11 @@
 pub mod execute;
 pub mod instantiate;
+pub mod migrate;
 pub mod module_ibc;
 pub mod sudo;
 
 pub use self::{
-    execute::execute_handler, instantiate::instantiate_handler, module_ibc::module_ibc_handler,
-    query::query_handler, sudo::sudo_handler,
+    execute::execute_handler, instantiate::instantiate_handler, migrate::migrate_handler,
+    module_ibc::module_ibc_handler, query::query_handler, sudo::sudo_handler,
 };
 pub mod query;
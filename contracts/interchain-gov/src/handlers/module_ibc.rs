@@ -1,14 +1,26 @@
 use abstract_adapter::objects::chain_name::ChainName;
 use abstract_adapter::sdk::AbstractResponse;
 use abstract_adapter::std::ibc::ModuleIbcMsg;
-use cosmwasm_std::{from_json, DepsMut, Env};
+use cosmwasm_std::{from_json, Binary, DepsMut, Env, Storage, SubMsg};
+use ibc_sync_state::SyncEvent;
 
 use crate::contract::{AdapterResult, InterchainGov};
-use crate::msg::InterchainGovIbcMsg;
+use crate::deposit::settle_deposit;
+use crate::handlers::execute::send_packets;
+use crate::membership::{
+    apply_invite_response, invite_members, record_invite_response, remove_member, reset_membership,
+};
+use crate::msg::{InterchainGovIbcMsg, InterchainGovIbcPacket};
+use crate::pause::{apply_pause_action, assert_not_paused, load_guardian};
+use crate::state_hash::{assert_parent_hash, commit_state, load_state_hash};
+use crate::treasury::{ibc_transfer, record_deposit, record_refund, spend_from_treasury};
+use crate::upgrade::assert_compatible_version;
+use crate::veto::{update_guardians, veto_by_proposal, veto_queued};
 
 use crate::state::{
-    Members, ProposalAction, Vote, ALLOW_JOINING_GOV, FINALIZED_PROPOSALS, MEMBERS_STATE_SYNC,
-    PROPOSAL_STATE_SYNC,
+    load_gov_config, Authority, Invite, Proposal, ProposalAction, StateCommit, Vote,
+    ALLOW_JOINING_GOV, DELEGATIONS, FINALIZED_PROPOSALS, GOV_CONFIG_SYNC, MEMBERS_STATE_SYNC,
+    PAUSE, PENDING_INVITES, PROPOSAL_STATE_SYNC, PROPOSAL_TWO_PHASE,
 };
 use crate::{InterchainGovError, MY_ADAPTER_ID};
 
@@ -18,24 +30,37 @@ pub fn module_ibc_handler(
     app: InterchainGov,
     ibc_msg: ModuleIbcMsg,
 ) -> AdapterResult {
-    println!("module_ibc_handler 1 : {:?}", ibc_msg);
-
     // First check that we received the message from the gov contract
     if ibc_msg.source_module.id().ne(MY_ADAPTER_ID) {
-        println!("UnauthorizedIbcModule: {:?}", ibc_msg.source_module.clone());
         return Err(InterchainGovError::UnauthorizedIbcModule(
             ibc_msg.source_module.clone(),
         ));
     };
 
-    let ibc_msg: InterchainGovIbcMsg = from_json(&ibc_msg.msg)?;
+    let source_chain = ibc_msg.client_chain.clone();
+    let InterchainGovIbcPacket {
+        version,
+        parent_hash,
+        msg: ibc_msg,
+    } = from_json(&ibc_msg.msg)?;
+    assert_compatible_version(&version)?;
 
-    println!("parsed_msg: {:?}", ibc_msg);
+    handle_ibc_msg(deps, &env, &app, source_chain, parent_hash, ibc_msg)
+}
 
-    let our_chain = ChainName::new(&env);
+fn handle_ibc_msg(
+    mut deps: DepsMut,
+    env: &Env,
+    app: &InterchainGov,
+    source_chain: ChainName,
+    parent_hash: Binary,
+    ibc_msg: InterchainGovIbcMsg,
+) -> AdapterResult {
+    let our_chain = ChainName::new(env);
 
     match ibc_msg {
-        InterchainGovIbcMsg::JoinGov { members } => {
+        InterchainGovIbcMsg::JoinGov { members, invitees } => {
+            assert_not_paused(deps.storage, None)?;
             // Check that the data has been finalized before.
             MEMBERS_STATE_SYNC.assert_finalized(deps.storage)?;
 
@@ -46,15 +71,45 @@ pub fn module_ibc_handler(
                 .members
                 .iter()
                 .all(|a| allowed_gov.members.contains(a))
+                || !members.members.contains(&source_chain)
             {
                 return Err(InterchainGovError::UnauthorizedIbcMessage {});
             }
 
-            // We just finalize this whenever we receive the proposal from a group we want to join
-            // This means we don't have to handle a Finalize vote here.
-            MEMBERS_STATE_SYNC.finalize_members(deps.storage, Some(members))?;
+            // Store the invite, our governance has to accept it before we join.
+            PENDING_INVITES.save(
+                deps.storage,
+                &source_chain,
+                &Invite {
+                    members,
+                    parent_hash,
+                    invitees,
+                    responses: vec![],
+                },
+            )?;
 
-            Ok(app.response("module_ibc"))
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "invite_received")
+                .add_attribute("inviter", source_chain.to_string()))
+        }
+        InterchainGovIbcMsg::InviteResponse { accepted } => {
+            let mut events = vec![];
+            if MEMBERS_STATE_SYNC.assert_finalized(deps.storage).is_err() {
+                // The members change waits on the invitees, applied once every one responded
+                events = apply_invite_response(deps.storage, &source_chain, accepted)?;
+            } else if !record_invite_response(deps.storage, &source_chain, accepted)? {
+                // Not invited together with us either, applied once we accept our invite
+                return Err(InterchainGovError::UnknownMember {
+                    member: source_chain,
+                });
+            }
+
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "invite_response")
+                .add_attribute("accepted", accepted.to_string())
+                .add_attributes(events.iter().flat_map(SyncEvent::to_attributes)))
         }
         InterchainGovIbcMsg::ProposeProposal {
             prop_hash,
@@ -68,46 +123,311 @@ pub fn module_ibc_handler(
                     actual: chain,
                 });
             }
+            assert_member(deps.storage, &source_chain)?;
+            assert_proposer(&prop, &source_chain)?;
+
+            assert_not_paused(deps.storage, Some(&prop))?;
+            load_gov_config(deps.storage)?.assert_voting_period(&prop, &env.block)?;
+            assert_parent_hash(deps.storage, &parent_hash)?;
 
             // update proposal state to "proposed". Member will vote `NoVote` on the proposal by default
-            PROPOSAL_STATE_SYNC.propose_kv_state(deps.storage, prop_hash, (prop, Vote::NoVote))?;
+            let event =
+                PROPOSAL_TWO_PHASE.on_prepare(deps.storage, prop_hash, (prop, Vote::NoVote))?;
             Ok(app
                 .response("module_ibc")
-                .add_attribute("action", "propose"))
+                .add_attribute("action", "propose")
+                .add_attributes(event.to_attributes()))
         }
         InterchainGovIbcMsg::FinalizeProposal { prop_hash: prop_id } => {
-            PROPOSAL_STATE_SYNC.finalize_kv_state(deps.storage, prop_id, None)?;
+            assert_parent_hash(deps.storage, &parent_hash)?;
+            let event = PROPOSAL_TWO_PHASE.on_commit(deps.storage, prop_id.clone())?;
+
+            // The commit is reverted if it didn't come from the proposing chain
+            let prop = PROPOSAL_STATE_SYNC.load(deps.storage, prop_id.clone())?.0;
+            assert_proposer(&prop, &source_chain)?;
+            commit_state(
+                deps.storage,
+                &StateCommit::Proposal {
+                    prop_id,
+                    prop: Box::new(prop),
+                },
+            )?;
             Ok(app
                 .response("module_ibc")
-                .add_attribute("action", "finalize"))
+                .add_attribute("action", "finalize")
+                .add_attributes(event.to_attributes()))
+        }
+        InterchainGovIbcMsg::AbortProposal { prop_hash } => {
+            // Dropping the proposal doesn't change the committed state, no parent hash to check
+            let event = PROPOSAL_TWO_PHASE.on_abort(deps.storage, prop_hash)?;
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "abort")
+                .add_attributes(event.iter().flat_map(SyncEvent::to_attributes)))
         }
         InterchainGovIbcMsg::ProposalResult {
             prop_hash: prop_id,
             outcome,
         } => {
+            assert_member(deps.storage, &source_chain)?;
+            assert_parent_hash(deps.storage, &parent_hash)?;
+            if FINALIZED_PROPOSALS.has(deps.storage, prop_id.clone()) {
+                return Err(InterchainGovError::ProposalExecuted(prop_id));
+            }
+
             let prop = PROPOSAL_STATE_SYNC.load(deps.storage, prop_id.clone())?.0;
+            // Only the proposing chain tallies the votes and reports the outcome
+            assert_proposer(&prop, &source_chain)?;
+            assert_not_paused(deps.storage, Some(&prop))?;
+            let deposit_msgs = settle_deposit(deps.storage, &prop_id, &prop, &outcome)?;
             // TODO: store each vote per chain
             FINALIZED_PROPOSALS.save(
                 deps.storage,
                 prop_id.clone(),
                 &(prop.clone(), outcome.clone()),
             )?;
+            commit_state(
+                deps.storage,
+                &StateCommit::Outcome {
+                    prop_id: prop_id.clone(),
+                    outcome: outcome.clone(),
+                },
+            )?;
 
-            // Execute the prop
-            match prop.action {
-                ProposalAction::UpdateMembers { members } => {
-                    // If new members exclude self, update members to only be self
-                    if !members.members.contains(&ChainName::new(&env)) {
-                        MEMBERS_STATE_SYNC.save_members(deps.storage, &Members::new(&env))?;
-                    }
-                    MEMBERS_STATE_SYNC.save_members(deps.storage, &members)?;
+            // Execute the prop, a vetoed one has no action
+            let action = prop.action_for(&outcome);
+            let action_msgs = match action.clone() {
+                _ if outcome.vetoed.is_some() => vec![],
+                ProposalAction::UpdateMembers { members } if outcome.passed => {
+                    // Like the executing chain, wait on the invitees' responses
+                    invite_members(deps.storage, env, members)?;
+                    vec![]
                 }
-                _ => {}
-            }
+                ProposalAction::RemoveMember { member } if outcome.passed => {
+                    remove_member(deps.storage, &member)?;
+                    vec![]
+                }
+                ProposalAction::SpendFromTreasury {
+                    chain,
+                    recipient,
+                    amount,
+                } if outcome.passed => {
+                    spend_from_treasury(deps.storage, deps.api, env, &chain, &recipient, &amount)?
+                }
+                ProposalAction::UpdateConfig { config } if outcome.passed => {
+                    GOV_CONFIG_SYNC.finalize_item_state(deps.storage, Some(config))?;
+                    vec![]
+                }
+                ProposalAction::Pause { .. } | ProposalAction::Unpause {} if outcome.passed => {
+                    apply_pause_action(deps.storage, &prop_id, &action)?;
+                    vec![]
+                }
+                ProposalAction::UpdateGuardians { guardians } if outcome.passed => {
+                    update_guardians(deps.storage, guardians)?;
+                    vec![]
+                }
+                ProposalAction::VetoProposal {
+                    prop_id: target,
+                    reason,
+                } if outcome.passed => {
+                    let (packets, deposit_msgs) =
+                        veto_by_proposal(deps.storage, env, &prop_id, target, reason)?;
+                    send_packets(deps.as_ref(), app, packets)?
+                        .into_iter()
+                        .chain(deposit_msgs)
+                        .map(SubMsg::new)
+                        .collect()
+                }
+                ProposalAction::IbcTransfer {
+                    from_chain,
+                    to_chain,
+                    recipient,
+                    amount,
+                } if outcome.passed => ibc_transfer(
+                    deps.branch(),
+                    env,
+                    app,
+                    &prop_id,
+                    &from_chain,
+                    &to_chain,
+                    &recipient,
+                    &amount,
+                )?,
+                _ => vec![],
+            };
             Ok(app
                 .response("module_ibc")
+                .add_messages(deposit_msgs)
+                .add_submessages(action_msgs)
                 .add_attribute("action", "proposal_result"))
         }
+        InterchainGovIbcMsg::LeaveGov {} => {
+            assert_member(deps.storage, &source_chain)?;
+            remove_member(deps.storage, &source_chain)?;
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "leave_gov")
+                .add_attribute("member", source_chain.to_string()))
+        }
+        InterchainGovIbcMsg::RemovedFromGov { prop_hash, outcome } => {
+            assert_member(deps.storage, &source_chain)?;
+            assert_parent_hash(deps.storage, &parent_hash)?;
+            if FINALIZED_PROPOSALS.has(deps.storage, prop_hash.clone()) {
+                return Err(InterchainGovError::ProposalExecuted(prop_hash));
+            }
+
+            // Only a passed proposal removing this chain removes it
+            let prop = PROPOSAL_STATE_SYNC.load(deps.storage, prop_hash.clone())?.0;
+            match prop.action_for(&outcome) {
+                ProposalAction::RemoveMember { member }
+                    if outcome.passed && member == our_chain => {}
+                _ => return Err(InterchainGovError::UnauthorizedIbcMessage {}),
+            }
+            let deposit_msgs = settle_deposit(deps.storage, &prop_hash, &prop, &outcome)?;
+            FINALIZED_PROPOSALS.save(deps.storage, prop_hash.clone(), &(prop, outcome.clone()))?;
+            commit_state(
+                deps.storage,
+                &StateCommit::Outcome {
+                    prop_id: prop_hash.clone(),
+                    outcome,
+                },
+            )?;
+
+            let refunds = reset_membership(deps.storage, env)?;
+            Ok(app
+                .response("module_ibc")
+                .add_messages(deposit_msgs)
+                .add_messages(refunds)
+                .add_attribute("action", "removed_from_gov")
+                .add_attribute("prop_id", prop_hash))
+        }
+        InterchainGovIbcMsg::ActionResult {
+            prop_hash,
+            result,
+            refunded,
+        } => {
+            // Only the chain executing the action reports its result
+            let (prop, mut outcome) = FINALIZED_PROPOSALS.load(deps.storage, prop_hash.clone())?;
+            match &prop.action_for(&outcome) {
+                ProposalAction::IbcTransfer { from_chain, .. } if from_chain == &source_chain => {}
+                _ => return Err(InterchainGovError::UnauthorizedIbcMessage {}),
+            }
+            // Failed transfers are no longer spent
+            record_refund(deps.storage, &source_chain, &refunded)?;
+            outcome.action_result = Some(result);
+            FINALIZED_PROPOSALS.save(deps.storage, prop_hash.clone(), &(prop, outcome))?;
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "action_result")
+                .add_attribute("prop_id", prop_hash))
+        }
+        InterchainGovIbcMsg::TreasuryDeposit { funds } => {
+            // The ledger isn't part of the committed state, no parent hash to check
+            assert_member(deps.storage, &source_chain)?;
+            record_deposit(deps.storage, &source_chain, &funds)?;
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "treasury_deposit")
+                .add_attribute("member", source_chain.to_string()))
+        }
+        InterchainGovIbcMsg::VetoProposal { prop_hash, veto } => {
+            // The veto only changes the committed state once accepted, no parent hash to check
+            let guardians = load_gov_config(deps.storage)?.guardians;
+            let Authority::Guardian(guardian) = &veto.by else {
+                return Err(InterchainGovError::UnauthorizedIbcMessage {});
+            };
+            if guardian.chain != source_chain
+                || guardians.find(&source_chain, &guardian.address).is_none()
+            {
+                return Err(InterchainGovError::UnauthorizedIbcMessage {});
+            }
+            let (packets, deposit_msgs) = veto_queued(deps.storage, env, prop_hash.clone(), veto)?;
+            let msgs = send_packets(deps.as_ref(), app, packets)?;
+            Ok(app
+                .response("module_ibc")
+                .add_messages(msgs)
+                .add_messages(deposit_msgs)
+                .add_attribute("action", "veto_proposal")
+                .add_attribute("prop_id", prop_hash))
+        }
+        InterchainGovIbcMsg::DelegationChanged { delegation } => {
+            // The delegations aren't part of the committed state, no parent hash to check
+            assert_member(deps.storage, &source_chain)?;
+            match delegation {
+                Some(delegation) => {
+                    assert_member(deps.storage, &delegation.delegate)?;
+                    DELEGATIONS.save(deps.storage, &source_chain, &delegation)?;
+                }
+                None => DELEGATIONS.remove(deps.storage, &source_chain),
+            }
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "delegation_changed")
+                .add_attribute("delegator", source_chain.to_string()))
+        }
+        InterchainGovIbcMsg::Paused { pause } => {
+            // The pause isn't part of the committed state, no parent hash to check
+            let Authority::Guardian(guardian) = &pause.by else {
+                return Err(InterchainGovError::UnauthorizedIbcMessage {});
+            };
+            if guardian.chain != source_chain
+                || load_guardian(deps.storage, &source_chain, &guardian.address).is_err()
+            {
+                return Err(InterchainGovError::UnauthorizedIbcMessage {});
+            }
+            PAUSE.save(deps.storage, &pause)?;
+            Ok(app
+                .response("module_ibc")
+                .add_attribute("action", "paused")
+                .add_attribute("guardian_chain", source_chain.to_string()))
+        }
+        InterchainGovIbcMsg::Batch { msgs } => {
+            let mut response = app.response("module_ibc").add_attribute("action", "batch");
+            let mut parent_hash = parent_hash;
+            for msg in msgs {
+                let before = load_state_hash(deps.storage)?;
+                let res = handle_ibc_msg(
+                    deps.branch(),
+                    env,
+                    app,
+                    source_chain.clone(),
+                    parent_hash.clone(),
+                    msg,
+                )?;
+                // Following messages are built on the state committed by the previous ones
+                let after = load_state_hash(deps.storage)?;
+                if after != before {
+                    parent_hash = after;
+                }
+                response = response
+                    .add_submessages(res.messages)
+                    .add_attributes(res.attributes)
+                    .add_events(res.events);
+            }
+            Ok(response)
+        }
         _ => Err(InterchainGovError::UnauthorizedIbcMessage {}),
     }
 }
+
+/// Messages about a proposal's lifecycle are only accepted from the chain that proposed it
+fn assert_proposer(prop: &Proposal, chain: &ChainName) -> AdapterResult<()> {
+    if &prop.proposer_chain != chain {
+        return Err(InterchainGovError::WrongChain {
+            expected: prop.proposer_chain.clone(),
+            actual: chain.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Only members of the governance are allowed to change its membership
+fn assert_member(storage: &dyn Storage, chain: &ChainName) -> AdapterResult<()> {
+    let members = MEMBERS_STATE_SYNC.load_members(storage)?;
+    if !members.members.contains(chain) {
+        return Err(InterchainGovError::UnknownMember {
+            member: chain.clone(),
+        });
+    }
+    Ok(())
+}
@@ -5,10 +5,20 @@ use crate::{
 };
 use abstract_adapter::objects::chain_name::ChainName;
 
+use crate::delegation::counted_votes;
 use crate::msg::{
-    MembersResponse, ProposalResponse, ProposalsResponse, VoteResponse, VoteResultsResponse,
+    DelegationsResponse, MembersResponse, PendingInvitesResponse, ProposalHashesResponse,
+    ProposalHistoryResponse, ProposalResponse, ProposalsResponse, QueuedProposalsResponse,
+    StateHashResponse, StatusResponse, SyncReportsResponse, TreasuryResponse, VoteResponse,
+    VoteResultsResponse,
 };
-use crate::state::{GovernanceVote, ProposalId, VOTE, VOTE_RESULTS};
+use crate::state::{
+    load_gov_config, ProposalId, VoteResult, DELEGATIONS, DEPOSITS, FINALIZED_PROPOSALS,
+    GOV_CONFIG_SYNC, MEMBERS_AT, PAUSE, PENDING_INVITES, QUEUED_PROPOSALS, SYNC_REPORTS, VOTE,
+    VOTE_RESULTS,
+};
+use crate::state_hash::load_state_hash;
+use crate::treasury::treasury_balances;
 use cosmwasm_std::{to_json_binary, Binary, Deps, Env, Order, StdResult};
 use ibc_sync_state::DataState;
 
@@ -21,6 +31,27 @@ pub fn query_handler(
     match msg {
         InterchainGovQueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         InterchainGovQueryMsg::Members {} => to_json_binary(&query_members(deps)?),
+        InterchainGovQueryMsg::MembersAt { epoch } => {
+            to_json_binary(&query_members_at(deps, epoch)?)
+        }
+        InterchainGovQueryMsg::PendingInvites {} => to_json_binary(&query_pending_invites(deps)?),
+        InterchainGovQueryMsg::ProposalHashes {} => to_json_binary(&query_proposal_hashes(deps)?),
+        InterchainGovQueryMsg::StateHash {} => to_json_binary(&StateHashResponse {
+            hash: load_state_hash(deps.storage)?,
+        }),
+        InterchainGovQueryMsg::SyncReports {} => to_json_binary(&query_sync_reports(deps)?),
+        InterchainGovQueryMsg::Treasury {} => to_json_binary(&TreasuryResponse {
+            balances: treasury_balances(deps.storage)?,
+        }),
+        InterchainGovQueryMsg::QueuedProposals {} => to_json_binary(&query_queued_proposals(deps)?),
+        InterchainGovQueryMsg::Delegations {} => to_json_binary(&DelegationsResponse {
+            delegations: DELEGATIONS
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<_>>()?,
+        }),
+        InterchainGovQueryMsg::Status {} => to_json_binary(&StatusResponse {
+            pause: PAUSE.may_load(deps.storage)?,
+        }),
         InterchainGovQueryMsg::Proposal { prop_id } => {
             to_json_binary(&query_proposal(deps, prop_id)?)
         }
@@ -33,6 +64,14 @@ pub fn query_handler(
             to_json_binary(&query_proposals(deps, proposal_ids)?)
         }
         InterchainGovQueryMsg::ListProposals {} => to_json_binary(&query_list_proposals(deps)?),
+        InterchainGovQueryMsg::ProposalHistory { prop_id } => {
+            to_json_binary(&ProposalHistoryResponse {
+                events: PROPOSAL_STATE_SYNC.history(deps.storage, prop_id)?,
+            })
+        }
+        InterchainGovQueryMsg::Deposit { prop_id } => {
+            to_json_binary(&DEPOSITS.may_load(deps.storage, prop_id)?)
+        }
         InterchainGovQueryMsg::ProposalState { prop_id } => {
             to_json_binary(&query_prop_state(deps, prop_id)?)
         }
@@ -83,11 +122,14 @@ fn query_props_state(deps: Deps) -> AdapterResult<ProposalStateResponse> {
 
     let states = states
         .into_iter()
-        .map(|((nk, k, i), change)| MapState {
-            namespace: nk,
-            proposal_id: k,
-            state: DataState::from_num(i).unwrap(),
-            change,
+        // Backups are stored next to the states
+        .filter_map(|((nk, k, i), change)| {
+            Some(MapState {
+                namespace: nk,
+                proposal_id: k,
+                state: DataState::from_num(i)?,
+                change,
+            })
         })
         .collect();
 
@@ -104,11 +146,14 @@ fn query_prop_state(deps: Deps, prop_id: ProposalId) -> AdapterResult<Option<Map
 
     let states: Vec<MapState> = states
         .into_iter()
-        .map(|((nk, k, i), change)| MapState {
-            namespace: nk,
-            proposal_id: k,
-            state: DataState::from_num(i).unwrap(),
-            change,
+        // Backups are stored next to the states
+        .filter_map(|((nk, k, i), change)| {
+            Some(MapState {
+                namespace: nk,
+                proposal_id: k,
+                state: DataState::from_num(i)?,
+                change,
+            })
         })
         .collect();
 
@@ -132,34 +177,117 @@ fn query_proposal(deps: Deps, prop_id: ProposalId) -> AdapterResult<ProposalResp
 
 fn query_members(deps: Deps) -> AdapterResult<MembersResponse> {
     let members = MEMBERS_STATE_SYNC.load_members(deps.storage)?;
+    let epoch = MEMBERS_STATE_SYNC.load_epoch(deps.storage)?;
 
-    Ok(MembersResponse { members })
+    Ok(MembersResponse { members, epoch })
 }
 
-fn query_config(_deps: Deps) -> AdapterResult<ConfigResponse> {
-    Ok(ConfigResponse {})
+fn query_members_at(deps: Deps, epoch: u64) -> AdapterResult<MembersResponse> {
+    let members = MEMBERS_AT.load(deps.storage, epoch)?;
+
+    Ok(MembersResponse { members, epoch })
 }
 
-fn query_vote(deps: Deps, env: Env, prop_id: ProposalId) -> StdResult<VoteResponse> {
-    let GovernanceVote { vote, governance } = VOTE.load(deps.storage, prop_id.clone())?;
+fn query_pending_invites(deps: Deps) -> AdapterResult<PendingInvitesResponse> {
+    let invites = PENDING_INVITES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PendingInvitesResponse { invites })
+}
+
+pub(crate) fn query_proposal_hashes(deps: Deps) -> AdapterResult<ProposalHashesResponse> {
+    let proposals = PROPOSAL_STATE_SYNC
+        .map()
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let executed = FINALIZED_PROPOSALS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ProposalHashesResponse {
+        proposals,
+        executed,
+    })
+}
+
+fn query_sync_reports(deps: Deps) -> AdapterResult<SyncReportsResponse> {
+    let reports = SYNC_REPORTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
 
+    let pending = reports
+        .iter()
+        .filter(|(_, report)| report.is_pending())
+        .map(|(chain, _)| chain.clone())
+        .collect();
+    let diverged = reports
+        .into_iter()
+        .filter(|(_, report)| report.is_diverged())
+        .collect();
+
+    Ok(SyncReportsResponse { diverged, pending })
+}
+
+fn query_queued_proposals(deps: Deps) -> AdapterResult<QueuedProposalsResponse> {
+    let proposals = QUEUED_PROPOSALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(QueuedProposalsResponse { proposals })
+}
+
+fn query_config(deps: Deps) -> AdapterResult<ConfigResponse> {
+    let pending = match GOV_CONFIG_SYNC.data_state(deps.storage) {
+        Some(_) => Some(GOV_CONFIG_SYNC.load_pending(deps.storage)?),
+        None => None,
+    };
+    Ok(ConfigResponse {
+        config: load_gov_config(deps.storage)?,
+        pending,
+    })
+}
+
+fn query_vote(deps: Deps, env: Env, prop_id: ProposalId) -> StdResult<VoteResponse> {
     Ok(VoteResponse {
-        vote,
-        governance,
+        vote: VOTE.may_load(deps.storage, prop_id.clone())?,
         prop_id,
         chain: ChainName::new(&env),
     })
 }
 
-fn query_vote_results(
-    deps: Deps,
-    _env: Env,
-    prop_id: ProposalId,
-) -> StdResult<VoteResultsResponse> {
+fn query_vote_results(deps: Deps, env: Env, prop_id: ProposalId) -> StdResult<VoteResultsResponse> {
     let results = VOTE_RESULTS
         .prefix(prop_id.clone())
         .range(deps.storage, None, None, Order::Ascending)
-        .collect::<StdResult<Vec<(ChainName, Option<GovernanceVote>)>>>()?;
+        .collect::<StdResult<Vec<(ChainName, VoteResult)>>>()?;
+
+    let counted = match PROPOSAL_STATE_SYNC
+        .map()
+        .may_load(deps.storage, prop_id.clone())?
+    {
+        Some((prop, _)) => {
+            let mut votes = vec![];
+            let our_chain = ChainName::new(&env);
+            if prop.members.members.contains(&our_chain) {
+                let vote = VOTE.may_load(deps.storage, prop_id.clone())?;
+                votes.push((our_chain, vote.map(|vote| vote.vote)));
+            }
+            votes.extend(
+                results
+                    .iter()
+                    .filter(|(chain, result)| {
+                        !result.is_pending() && prop.members.members.contains(chain)
+                    })
+                    .map(|(chain, result)| (chain.clone(), result.vote())),
+            );
+            counted_votes(deps.storage, &prop, votes)?
+        }
+        None => vec![],
+    };
 
-    Ok(crate::msg::VoteResultsResponse { prop_id, results })
+    Ok(crate::msg::VoteResultsResponse {
+        prop_id,
+        results,
+        counted,
+    })
 }
@@ -4,8 +4,9 @@ use cosmwasm_std::{Binary, DepsMut, Env, StdError, Storage};
 
 use neutron_query::icq::IcqInterface;
 
-use crate::msg::InterchainGovSudoMsg;
-use crate::state::{TallyResult, GOV_VOTE_QUERIES, PENDING_QUERIES};
+use crate::msg::{InterchainGovSudoMsg, RequestPacket};
+use crate::state::{TallyResult, GOV_VOTE_QUERIES, PENDING_QUERIES, TRANSFER_PACKETS};
+use crate::treasury::settle_transfer;
 use crate::{
     contract::{AdapterResult, InterchainGov},
     InterchainGovError,
@@ -13,7 +14,7 @@ use crate::{
 
 pub fn sudo_handler(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     app: InterchainGov,
     msg: InterchainGovSudoMsg,
 ) -> AdapterResult {
@@ -69,8 +70,39 @@ pub fn sudo_handler(
             } else {
                 unimplemented!("No proposals found")
             }
+            Ok(app.response("instantiate"))
+        }
+        InterchainGovSudoMsg::Response { request, .. } => {
+            settle_transfer_packet(deps, env, app, request, None)
+        }
+        InterchainGovSudoMsg::Error { request, details } => {
+            settle_transfer_packet(deps, env, app, request, Some(details))
+        }
+        InterchainGovSudoMsg::Timeout { request } => {
+            settle_transfer_packet(deps, env, app, request, Some("timeout".to_string()))
         }
     }
+}
+
+/// Settle the treasury transfer of an acked or timed out packet
+fn settle_transfer_packet(
+    deps: DepsMut,
+    env: Env,
+    app: InterchainGov,
+    request: RequestPacket,
+    failure: Option<String>,
+) -> AdapterResult {
+    let (Some(channel), Some(sequence)) = (request.source_channel, request.sequence) else {
+        return Err(StdError::generic_err("packet without source channel or sequence").into());
+    };
+    let (prop_id, coin) = TRANSFER_PACKETS.load(deps.storage, (&channel, sequence))?;
+    TRANSFER_PACKETS.remove(deps.storage, (&channel, sequence));
 
-    Ok(app.response("instantiate"))
+    let executed = failure.is_none();
+    let msgs = settle_transfer(deps, &env, &app, &prop_id, coin, failure)?;
+    Ok(app
+        .response("transfer_ack")
+        .add_messages(msgs)
+        .add_attribute("prop_id", prop_id)
+        .add_attribute("executed", executed.to_string()))
 }
@@ -4,11 +4,10 @@ use cosmwasm_std::{from_json, DepsMut, Env, MessageInfo};
 
 use crate::contract::{AdapterResult, InterchainGov};
 use crate::msg::InterchainGovIbcCallbackMsg;
-use crate::state::{MEMBERS_STATE_SYNC, PROPOSAL_STATE_SYNC};
+use crate::state::PROPOSAL_TWO_PHASE;
 use crate::InterchainGovError;
 
-/// Get a callback when a proposal is finalized
-/// TODO: figure out how to abstract this state transition
+/// Get a callback when a proposal is finalized or aborted on a member
 pub fn finalize_callback(
     deps: DepsMut,
     _env: Env,
@@ -16,7 +15,6 @@ pub fn finalize_callback(
     app: InterchainGov,
     ibc_msg: IbcResponseMsg,
 ) -> AdapterResult {
-    println!("finalize_callback");
     match ibc_msg {
         IbcResponseMsg {
             id: _,
@@ -27,14 +25,13 @@ pub fn finalize_callback(
             match callback_msg {
                 InterchainGovIbcCallbackMsg::FinalizeProposal {
                     proposed_to,
-                    prop_hash: _prop_id,
+                    prop_hash: prop_id,
+                }
+                | InterchainGovIbcCallbackMsg::AbortProposal {
+                    proposed_to,
+                    prop_hash: prop_id,
                 } => {
-                    PROPOSAL_STATE_SYNC.apply_ack(deps.storage, proposed_to)?;
-
-                    if !PROPOSAL_STATE_SYNC.has_outstanding_acks(deps.storage)? {
-                        // finalize my proposal
-                        MEMBERS_STATE_SYNC.finalize_members(deps.storage, None)?;
-                    }
+                    PROPOSAL_TWO_PHASE.on_ack(deps.storage, prop_id, proposed_to)?;
                 }
                 // Wrong callback message
                 _ => unimplemented!(),
@@ -49,7 +46,5 @@ pub fn finalize_callback(
         _ => panic!("{:?}", ibc_msg),
     }
 
-    println!("finalize_callback done");
-
     Ok(app.response("finalize_callback"))
 }
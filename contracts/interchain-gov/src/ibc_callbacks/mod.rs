@@ -1,4 +1,5 @@
 pub mod finalize;
+mod sync_check;
 mod vote_result;
 
 pub mod proposal;
@@ -6,7 +7,9 @@ pub mod proposal;
 pub const PROPOSE_CALLBACK_ID: &str = "propose_callback";
 pub const FINALIZE_CALLBACK_ID: &str = "finalize_callback";
 pub const REGISTER_VOTE_ID: &str = "register_vote";
+pub const SYNC_CHECK_ID: &str = "sync_check";
 
 pub use self::{
-    finalize::finalize_callback, proposal::proposal_callback, vote_result::vote_result_callback,
+    finalize::finalize_callback, proposal::proposal_callback, sync_check::sync_check_callback,
+    vote_result::vote_result_callback,
 };
@@ -1,11 +1,17 @@
 use abstract_adapter::sdk::AbstractResponse;
 use abstract_adapter::std::ibc::{CallbackResult, IbcResponseMsg};
-use cosmwasm_std::{from_json, DepsMut, Env, MessageInfo};
+use cosmwasm_std::{from_json, DepsMut, Env, MessageInfo, Storage};
+use ibc_sync_state::SyncEvent;
 
 use crate::contract::{AdapterResult, InterchainGov};
 
+use crate::deposit::refund_deposit;
+use crate::handlers::execute::proposal_packet_msgs;
 use crate::msg::InterchainGovIbcCallbackMsg;
-use crate::state::{MEMBERS_STATE_SYNC, PROPOSAL_STATE_SYNC};
+use crate::state::{
+    ProposalAction, FINALIZED_PROPOSALS, GOV_CONFIG_SYNC, PROPOSAL_STATE_SYNC, PROPOSAL_TWO_PHASE,
+};
+use crate::state_hash::load_state_hash;
 use crate::InterchainGovError;
 
 /// Get a callback when a proposal is synced
@@ -16,59 +22,111 @@ pub fn proposal_callback(
     app: InterchainGov,
     ibc_msg: IbcResponseMsg,
 ) -> AdapterResult {
-    match ibc_msg {
-        IbcResponseMsg {
-            id: _,
-            msg: Some(callback_msg),
-            result: CallbackResult::Execute { result: Ok(_), .. },
-        } => {
-            let callback_msg: InterchainGovIbcCallbackMsg = from_json(callback_msg)?;
-            println!("Decoded callback: {:?}", callback_msg);
-
-            match callback_msg {
-                InterchainGovIbcCallbackMsg::JoinGov { proposed_to } => {
-                    MEMBERS_STATE_SYNC.apply_ack(deps.storage, proposed_to)?;
-                    // finalize my proposal
+    let IbcResponseMsg {
+        msg: Some(callback_msg),
+        result,
+        ..
+    } = ibc_msg
+    else {
+        return Err(InterchainGovError::IbcFailed(
+            "missing callback".to_string(),
+        ));
+    };
+    // A packet that failed fatally on the member is handled like a failed one
+    let result = match result {
+        CallbackResult::Execute { result, .. } => result,
+        CallbackResult::FatalError(error) => Err(error),
+        CallbackResult::Query { .. } => {
+            return Err(InterchainGovError::IbcFailed(
+                "unexpected query callback".to_string(),
+            ))
+        }
+    };
+    let callback_msg: InterchainGovIbcCallbackMsg = from_json(callback_msg)?;
+    if let InterchainGovIbcCallbackMsg::ProposeProposal {
+        prop_hash: prop_id,
+        proposed_to,
+    } = callback_msg
+    {
+        // A member that failed to store the proposal votes to abort it
+        let step = PROPOSAL_TWO_PHASE.on_vote(
+            deps.storage,
+            prop_id.clone(),
+            proposed_to,
+            result.into(),
+        )?;
+        let parent_hash = load_state_hash(deps.storage)?;
+        let abort_msgs = proposal_packet_msgs(deps.as_ref(), &app, parent_hash, step.packets)?;
+        // An aborted proposal is dropped, its deposit is refunded
+        let aborted = PROPOSAL_STATE_SYNC
+            .data_state(deps.storage, prop_id.clone())
+            .is_none()
+            && !PROPOSAL_STATE_SYNC.has(deps.storage, prop_id.clone());
+        let refunds = if aborted {
+            refund_deposit(deps.storage, &prop_id)?
+        } else {
+            vec![]
+        };
 
-                    if !MEMBERS_STATE_SYNC.has_outstanding_acks(deps.storage)? {
-                        MEMBERS_STATE_SYNC.finalize_members(deps.storage, None)?;
-                    }
-                }
-                InterchainGovIbcCallbackMsg::ProposeProposal {
-                    prop_hash: prop_id,
-                    proposed_to,
-                    ..
-                } => {
-                    PROPOSAL_STATE_SYNC.apply_ack(deps.storage, proposed_to)?;
+        // TODO: finalize once prepared after testing
+        return Ok(app
+            .response("proposal_callback")
+            .add_messages(abort_msgs)
+            .add_messages(refunds)
+            .add_attributes(step.events.iter().flat_map(SyncEvent::to_attributes)));
+    }
 
-                    if !PROPOSAL_STATE_SYNC.has_outstanding_acks(deps.storage)? {
-                        PROPOSAL_STATE_SYNC.finalize_kv_state(
-                            deps.storage,
-                            prop_id.clone(),
-                            None,
-                        )?;
+    // Other packets are expected to succeed
+    result.map_err(InterchainGovError::IbcFailed)?;
+    let events = apply_delivered(deps.storage, callback_msg)?;
+    Ok(app
+        .response("proposal_callback")
+        .add_attributes(events.iter().flat_map(SyncEvent::to_attributes)))
+}
 
-                        // TODO: re-enable after testing
-                        // return finalize(deps, env, info, app, prop_id);
-                    }
-                }
-                InterchainGovIbcCallbackMsg::ProposalResult {
-                    prop_hash: _,
-                    proposed_to,
-                } => {
-                    PROPOSAL_STATE_SYNC.apply_ack(deps.storage, proposed_to)?;
+/// Apply the ack of a delivered packet
+fn apply_delivered(
+    storage: &mut dyn Storage,
+    callback_msg: InterchainGovIbcCallbackMsg,
+) -> AdapterResult<Vec<SyncEvent>> {
+    match callback_msg {
+        InterchainGovIbcCallbackMsg::JoinGov { proposed_to: _ } => {
+            // The invite was delivered. The members only change once every invitee's
+            // governance answered its invite, see `InviteResponse`.
+            Ok(vec![])
+        }
+        InterchainGovIbcCallbackMsg::ProposalResult {
+            prop_hash: prop_id,
+            proposed_to,
+        } => {
+            let mut events = vec![];
+            events.extend(PROPOSAL_STATE_SYNC.apply_ack(
+                storage,
+                prop_id.clone(),
+                proposed_to.clone(),
+            )?);
+            // A passed config update is applied once every member got the result
+            let (prop, outcome) = FINALIZED_PROPOSALS.load(storage, prop_id)?;
+            if let (ProposalAction::UpdateConfig { .. }, true) =
+                (prop.action_for(&outcome), outcome.passed)
+            {
+                events.extend(GOV_CONFIG_SYNC.apply_ack(storage, proposed_to)?);
+                if !GOV_CONFIG_SYNC.has_outstanding_acks(storage)? {
+                    events.push(GOV_CONFIG_SYNC.finalize_item_state(storage, None)?);
                 }
-                _ => unimplemented!(),
             }
+            Ok(events)
         }
-        IbcResponseMsg {
-            result: CallbackResult::Execute { result: Err(e), .. },
-            ..
-        } => {
-            return Err(InterchainGovError::IbcFailed(e));
+        // The messages of a batch are delivered together
+        InterchainGovIbcCallbackMsg::Batch { callbacks } => {
+            let mut events = vec![];
+            for callback_msg in callbacks {
+                events.extend(apply_delivered(storage, callback_msg)?);
+            }
+            Ok(events)
         }
-        _ => panic!("unexpected callback result"),
+        _ => Err(InterchainGovError::IbcFailed(
+            "unexpected callback".to_string(),
+        )),
     }
-
-    Ok(app.response("proposal_callback"))
 }
@@ -0,0 +1,90 @@
+use abstract_adapter::sdk::AbstractResponse;
+use abstract_adapter::std::ibc::{CallbackResult, IbcResponseMsg};
+use cosmwasm_std::{from_json, DepsMut, Env, MessageInfo, QueryRequest, WasmQuery};
+
+use crate::contract::{AdapterResult, InterchainGov};
+use crate::handlers::query::query_proposal_hashes;
+use crate::msg::{
+    InterchainGovIbcCallbackMsg, InterchainGovQueryMsg, MembersResponse, ProposalHashesResponse,
+    QueryMsg,
+};
+use crate::state::{StateDiff, MEMBERS_STATE_SYNC, SYNC_REPORTS};
+use crate::InterchainGovError;
+
+/// Compare the state reported by a member with our own and store the difference
+pub fn sync_check_callback(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    app: InterchainGov,
+    ibc_msg: IbcResponseMsg,
+) -> AdapterResult {
+    let chain = match ibc_msg.msg {
+        Some(callback_msg) => match from_json(callback_msg)? {
+            InterchainGovIbcCallbackMsg::SyncCheck { chain } => chain,
+            _ => {
+                return Err(InterchainGovError::IbcFailed(
+                    "unexpected callback".to_string(),
+                ))
+            }
+        },
+        None => {
+            return Err(InterchainGovError::IbcFailed(
+                "missing callback".to_string(),
+            ))
+        }
+    };
+
+    let (query, query_result) = match ibc_msg.result {
+        CallbackResult::Query { query, result } => {
+            let result = result.map_err(|e| InterchainGovError::IbcFailed(e.error))?;
+            let query_result = result
+                .first()
+                .cloned()
+                .ok_or_else(|| InterchainGovError::IbcFailed("empty query result".to_string()))?;
+            (query, query_result)
+        }
+        CallbackResult::Execute { .. } => {
+            return Err(InterchainGovError::IbcFailed(
+                "unexpected execute callback".to_string(),
+            ))
+        }
+        CallbackResult::FatalError(e) => return Err(InterchainGovError::IbcFailed(e)),
+    };
+
+    // Retrieve which state was queried from the original message
+    let unexpected_query = || InterchainGovError::IbcFailed("unexpected query".to_string());
+    let query_msg = match query {
+        QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => match from_json(msg)? {
+            QueryMsg::Module(query_msg) => query_msg,
+            _ => return Err(unexpected_query()),
+        },
+        _ => return Err(unexpected_query()),
+    };
+
+    // The report was dropped by a newer sync check
+    let Some(mut report) = SYNC_REPORTS.may_load(deps.storage, &chain)? else {
+        return Ok(app.response("sync_check_callback"));
+    };
+
+    match query_msg {
+        InterchainGovQueryMsg::Members {} => {
+            let remote: MembersResponse = from_json(query_result)?;
+            let local = MEMBERS_STATE_SYNC.load_members(deps.storage)?;
+            report.members = Some(StateDiff::new(&local.members, &remote.members.members));
+        }
+        InterchainGovQueryMsg::ProposalHashes {} => {
+            let remote: ProposalHashesResponse = from_json(query_result)?;
+            let local = query_proposal_hashes(deps.as_ref())?;
+            report.proposals = Some(StateDiff::new(&local.proposals, &remote.proposals));
+            report.executed = Some(StateDiff::new(&local.executed, &remote.executed));
+        }
+        _ => return Err(unexpected_query()),
+    }
+    SYNC_REPORTS.save(deps.storage, &chain, &report)?;
+
+    Ok(app
+        .response("sync_check_callback")
+        .add_attribute("chain", chain.to_string())
+        .add_attribute("diverged", report.is_diverged().to_string()))
+}
@@ -4,7 +4,7 @@ use cosmwasm_std::{from_json, DepsMut, Env, MessageInfo, QueryRequest, WasmQuery
 
 use crate::contract::{AdapterResult, InterchainGov};
 use crate::msg::{InterchainGovQueryMsg, QueryMsg, VoteResponse};
-use crate::state::{GovernanceVote, VOTE_RESULTS};
+use crate::state::{VoteResult, VOTE_RESULTS};
 use crate::InterchainGovError;
 
 /// Get a callback when a proposal is finalized
@@ -16,13 +16,9 @@ pub fn vote_result_callback(
     app: InterchainGov,
     ibc_msg: IbcResponseMsg,
 ) -> AdapterResult {
-    println!("finalize_callback");
-
     match ibc_msg.result.clone() {
         CallbackResult::Query { query, ref result } => {
-            println!("Query result");
             if result.is_err() {
-                println!("Query error");
                 return Err(InterchainGovError::IbcFailed("failed".to_string()));
             }
 
@@ -46,7 +42,6 @@ pub fn vote_result_callback(
 
             // Get the result of the query
             let query_results = result.clone().unwrap();
-            println!("Query results: {:?}", query_results);
             // TODO: check length
             let query_result = query_results.first().unwrap();
             let query_result: VoteResponse = from_json(query_result)?;
@@ -57,20 +52,17 @@ pub fn vote_result_callback(
             VOTE_RESULTS.update(
                 deps.storage,
                 (prop_id.clone(), &chain),
-                |prev_res| -> Result<Option<GovernanceVote>, InterchainGovError> {
+                |prev_res| -> Result<VoteResult, InterchainGovError> {
                     match prev_res {
                         Some(prev) => match prev {
-                            Some(_prev) => Err(InterchainGovError::ExistingVoteResult {
+                            VoteResult::Pending => Ok(match query_result.vote {
+                                Some(vote) => VoteResult::Cast(vote),
+                                None => VoteResult::NotCast,
+                            }),
+                            _ => Err(InterchainGovError::ExistingVoteResult {
                                 prop_id: prop_id.clone(),
                                 chain: chain.clone(),
                             }),
-                            None => {
-                                println!("Vote response added");
-                                Ok(Some(GovernanceVote::new(
-                                    query_result.governance,
-                                    query_result.vote,
-                                )))
-                            }
                         },
                         None => Err(InterchainGovError::UnrequestedVote {
                             prop_id: prop_id.clone(),
@@ -87,7 +79,6 @@ pub fn vote_result_callback(
             unreachable!("vote_result Execute callback")
         }
         CallbackResult::FatalError(e) => {
-            println!("Fatal error");
             return Err(InterchainGovError::IbcFailed(e));
         }
     }
@@ -1,12 +1,20 @@
 pub mod api;
 pub mod contract;
+mod delegation;
 mod dependencies;
+mod deposit;
 pub mod error;
 mod handlers;
 mod ibc_callbacks;
+mod membership;
 pub mod msg;
+mod pause;
 pub mod replies;
 pub mod state;
+mod state_hash;
+mod treasury;
+mod upgrade;
+mod veto;
 
 pub use contract::interface::InterchainGovInterface;
 pub use error::InterchainGovError;
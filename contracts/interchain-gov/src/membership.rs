@@ -0,0 +1,237 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::{Binary, CosmosMsg, Env, Order, StdResult, Storage};
+use ibc_sync_state::SyncEvent;
+
+use crate::contract::AdapterResult;
+use crate::delegation::remove_delegations;
+use crate::deposit::refund_deposit;
+use crate::state::{
+    Invite, Members, ProposalId, StateCommit, DELEGATIONS, FINALIZED_PROPOSALS, GOV_CONFIG_SYNC,
+    GOV_VOTE_QUERIES, MEMBERS_STATE_SYNC, PAUSE, PENDING_INVITES, PROPOSAL_STATE_SYNC,
+    PROPOSAL_TWO_PHASE, QUEUED_PROPOSALS, VOTE, VOTE_RESULTS,
+};
+use crate::state_hash::commit_state;
+use crate::InterchainGovError;
+
+/// Commit the current members to the state hash.
+///
+/// Called after every change of the membership, so each member commits the same members in the
+/// same order.
+pub(crate) fn commit_members(storage: &mut dyn Storage) -> StdResult<Binary> {
+    let members = MEMBERS_STATE_SYNC.load_members(storage)?;
+    commit_state(storage, &StateCommit::Members { members })
+}
+
+/// Change the members of the governance to `members`.
+///
+/// Chains that aren't members yet are invited, the change only applies once each of them responded
+/// to its invite, see [`apply_invite_response`]. Returns the invited chains.
+pub(crate) fn invite_members(
+    storage: &mut dyn Storage,
+    env: &Env,
+    members: Members,
+) -> AdapterResult<Vec<ChainName>> {
+    // If new members exclude self, update members to only be self
+    if !members.members.contains(&ChainName::new(env)) {
+        MEMBERS_STATE_SYNC.save_members(storage, &Members::new(env))?;
+        commit_members(storage)?;
+        return Ok(vec![]);
+    }
+
+    let current = MEMBERS_STATE_SYNC.load_members(storage)?;
+    let invitees = members
+        .members
+        .iter()
+        .filter(|m| !current.members.contains(m))
+        .cloned()
+        .collect::<Vec<_>>();
+    if invitees.is_empty() {
+        MEMBERS_STATE_SYNC.save_members(storage, &members)?;
+        commit_members(storage)?;
+    } else {
+        MEMBERS_STATE_SYNC.initiate_members(storage, members, invitees.clone())?;
+    }
+    Ok(invitees)
+}
+
+/// Apply the response of an invited chain to the pending members change.
+///
+/// A chain that rejected is left out. Once every invitee responded the members are finalized and
+/// committed.
+pub(crate) fn apply_invite_response(
+    storage: &mut dyn Storage,
+    invitee: &ChainName,
+    accepted: bool,
+) -> AdapterResult<Vec<SyncEvent>> {
+    let mut events = vec![];
+    if !accepted {
+        events.push(MEMBERS_STATE_SYNC.drop_proposed_member(storage, invitee)?);
+    }
+    let Some(ack) = MEMBERS_STATE_SYNC.apply_ack(storage, invitee.clone())? else {
+        return Err(InterchainGovError::UnknownMember {
+            member: invitee.clone(),
+        });
+    };
+    events.push(ack);
+    if !MEMBERS_STATE_SYNC.has_outstanding_acks(storage)? {
+        events.push(MEMBERS_STATE_SYNC.finalize_members(storage, None)?);
+        commit_members(storage)?;
+    }
+    Ok(events)
+}
+
+/// Join the governance of an accepted invite.
+///
+/// The members only change once the other invitees responded too, the responses that arrived
+/// before ours were recorded in the invite.
+pub(crate) fn join_members(
+    storage: &mut dyn Storage,
+    env: &Env,
+    invite: Invite,
+) -> AdapterResult<Vec<SyncEvent>> {
+    let our_chain = ChainName::new(env);
+    let mut members = invite.members;
+    for (chain, accepted) in &invite.responses {
+        if !accepted {
+            members.members.retain(|m| m != chain);
+        }
+    }
+    let outstanding = invite
+        .invitees
+        .into_iter()
+        .filter(|chain| chain != &our_chain && !invite.responses.iter().any(|(c, _)| c == chain))
+        .collect::<Vec<_>>();
+
+    if outstanding.is_empty() {
+        let event = MEMBERS_STATE_SYNC.finalize_members(storage, Some(members))?;
+        commit_members(storage)?;
+        Ok(vec![event])
+    } else {
+        Ok(vec![MEMBERS_STATE_SYNC.initiate_members(
+            storage,
+            members,
+            outstanding,
+        )?])
+    }
+}
+
+/// Record the response of a chain invited together with us, for the invites we didn't answer yet.
+/// Returns false if no such invite exists.
+pub(crate) fn record_invite_response(
+    storage: &mut dyn Storage,
+    invitee: &ChainName,
+    accepted: bool,
+) -> StdResult<bool> {
+    let invites = PENDING_INVITES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut recorded = false;
+    for (inviter, mut invite) in invites {
+        if invite.invitees.contains(invitee) {
+            invite.responses.push((invitee.clone(), accepted));
+            PENDING_INVITES.save(storage, &inviter, &invite)?;
+            recorded = true;
+        }
+    }
+    Ok(recorded)
+}
+
+/// Remove a member from the governance.
+///
+/// The member's vote results on proposals without an outcome are dropped so tallies don't wait on it,
+/// and it is removed from any outstanding acks. Proposals that were only waiting on the departed
+/// member's vote are finalized. The remaining members are committed.
+pub(crate) fn remove_member(storage: &mut dyn Storage, member: &ChainName) -> AdapterResult<()> {
+    // A pending member change that only waited on the departed member can be finalized
+    MEMBERS_STATE_SYNC.apply_ack(storage, member.clone())?;
+    if MEMBERS_STATE_SYNC.assert_finalized(storage).is_err()
+        && !MEMBERS_STATE_SYNC.has_outstanding_acks(storage)?
+    {
+        MEMBERS_STATE_SYNC.finalize_members(storage, None)?;
+    }
+
+    // Same for a config update
+    if GOV_CONFIG_SYNC
+        .apply_ack(storage, member.clone())?
+        .is_some()
+        && !GOV_CONFIG_SYNC.has_outstanding_acks(storage)?
+    {
+        GOV_CONFIG_SYNC.finalize_item_state(storage, None)?;
+    }
+
+    let mut members = MEMBERS_STATE_SYNC.load_members(storage)?;
+    members.members.retain(|m| m != member);
+    MEMBERS_STATE_SYNC.save_members(storage, &members)?;
+    remove_delegations(storage, member)?;
+
+    // Drop the votes of the member on open proposals
+    let open_votes = VOTE_RESULTS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(ProposalId, ChainName)>>>()?
+        .into_iter()
+        .filter(|(prop_id, chain)| {
+            chain == member && !FINALIZED_PROPOSALS.has(storage, prop_id.clone())
+        })
+        .collect::<Vec<_>>();
+    for (prop_id, chain) in open_votes {
+        VOTE_RESULTS.remove(storage, (prop_id.clone(), &chain));
+        GOV_VOTE_QUERIES.remove(storage, (prop_id, &chain));
+    }
+
+    // Don't wait on the member in open proposal transactions anymore
+    for prop_id in PROPOSAL_TWO_PHASE.pending_transactions(storage)? {
+        PROPOSAL_TWO_PHASE.drop_participant(storage, prop_id, member)?;
+    }
+    commit_members(storage)?;
+    Ok(())
+}
+
+/// Leave the governance locally.
+///
+/// Every proposal that didn't get an outcome yet is dropped together with its (pending) state and votes.
+/// Finalized proposals are kept for reference. Afterwards this chain is the only member.
+/// Returns the refunds of the deposits held for the dropped proposals.
+pub(crate) fn reset_membership(
+    storage: &mut dyn Storage,
+    env: &Env,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let mut open_props = PROPOSAL_STATE_SYNC
+        .map()
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<ProposalId>>>()?;
+    open_props.extend(PROPOSAL_STATE_SYNC.pending_keys(storage)?);
+    open_props.retain(|prop_id| !FINALIZED_PROPOSALS.has(storage, prop_id.clone()));
+
+    let mut refunds = vec![];
+    for prop_id in open_props {
+        refunds.extend(refund_deposit(storage, &prop_id)?);
+        QUEUED_PROPOSALS.remove(storage, prop_id.clone());
+        PROPOSAL_TWO_PHASE.clear(storage, prop_id.clone());
+        PROPOSAL_STATE_SYNC.map().remove(storage, prop_id.clone());
+        VOTE.remove(storage, prop_id.clone());
+
+        let chains = VOTE_RESULTS
+            .prefix(prop_id.clone())
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<ChainName>>>()?;
+        for chain in chains {
+            VOTE_RESULTS.remove(storage, (prop_id.clone(), &chain));
+            GOV_VOTE_QUERIES.remove(storage, (prop_id.clone(), &chain));
+        }
+    }
+    // Nobody is left to ack the finalized ones
+    for prop_id in PROPOSAL_TWO_PHASE.pending_transactions(storage)? {
+        PROPOSAL_TWO_PHASE.clear(storage, prop_id);
+    }
+    PROPOSAL_STATE_SYNC.clear_outstanding_acks(storage)?;
+    if GOV_CONFIG_SYNC.data_state(storage).is_some() {
+        GOV_CONFIG_SYNC.finalize_item_state(storage, None)?;
+        GOV_CONFIG_SYNC.set_outstanding_acks(storage, vec![])?;
+    }
+    // The pause and delegations belonged to the governance we left
+    PAUSE.remove(storage);
+    DELEGATIONS.clear(storage);
+
+    MEMBERS_STATE_SYNC.save_members(storage, &Members::new(env))?;
+    Ok(refunds)
+}
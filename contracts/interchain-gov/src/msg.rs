@@ -1,11 +1,13 @@
 use crate::{
     contract::InterchainGov,
     state::{Members, ProposalOutcome},
+    ADAPTER_VERSION,
 };
 
 use abstract_adapter::objects::chain_name::ChainName;
 use cosmwasm_schema::QueryResponses;
-use ibc_sync_state::{DataState, StateChange};
+use cosmwasm_std::{Binary, Coin};
+use ibc_sync_state::{DataState, StateChange, SyncEvent};
 
 // This is used for type safety and re-exporting the contract endpoint structs.
 abstract_adapter::adapter_msg_types!(
@@ -13,12 +15,20 @@ abstract_adapter::adapter_msg_types!(
     InterchainGovExecuteMsg,
     InterchainGovQueryMsg
 );
-use crate::state::{Governance, GovernanceVote, Proposal, ProposalId, ProposalMsg, Vote};
+use crate::state::{
+    ActionResult, CountedVote, Delegation, Deposit, DepositConfigMsg, GovConfig, Governance,
+    GovernanceVote, Invite, Pause, Proposal, ProposalId, ProposalMsg, QueuedProposal, SyncReport,
+    Veto, Vote, VoteResult,
+};
 
 /// App instantiate message
 #[cosmwasm_schema::cw_serde]
 pub struct InterchainGovInstantiateMsg {
+    /// Proxy of the account governed by this adapter, it holds the treasury funds of this chain
+    pub account: String,
     pub accept_proposal_from_gov: Members,
+    /// Deposit required to create a proposal, part of the initial [`GovConfig`]
+    pub deposit: Option<DepositConfigMsg>,
 }
 
 /// App execute messages
@@ -39,6 +49,21 @@ pub enum InterchainGovExecuteMsg {
     Execute {
         prop_id: ProposalId,
     },
+    /// Execute a passed proposal once its timelock expired
+    ExecuteQueued {
+        prop_id: ProposalId,
+    },
+    /// Called by a guardian to veto a passed proposal during its timelock.
+    /// Vetoes of proposals created on another chain are forwarded to that chain.
+    Veto {
+        prop_id: ProposalId,
+        reason: String,
+    },
+    /// Called by a guardian to pause the governance on every member.
+    /// Only a passed `Unpause` proposal lifts the pause.
+    Pause {
+        reason: String,
+    },
     ///Called by gov to vote on a proposal
     VoteProposal {
         prop_id: String,
@@ -65,10 +90,66 @@ pub enum InterchainGovExecuteMsg {
     TemporaryRegisterRemoteGovModuleAddrs {
         modules: Vec<(ChainName, String)>,
     },
+    /// Called by gov to accept a pending invite to join the governance of `inviter`.
+    /// Membership only becomes final on the inviting side once this is acknowledged.
+    AcceptInvite {
+        inviter: ChainName,
+    },
+    /// Called by gov to reject a pending invite to join the governance of `inviter`.
+    RejectInvite {
+        inviter: ChainName,
+    },
+    /// Query every member's members and proposals over IBC and compare them with our own state.
+    /// The differences are reported through the `SyncReports` query.
+    SyncCheck {},
+    /// Called by gov to leave the interchain governance.
+    /// Other members are notified and all proposals without an outcome are dropped locally.
+    LeaveGovernance {},
+    /// Called by gov to pledge funds of the account to the shared treasury.
+    /// The funds stay on the account and can only be spent by a `SpendFromTreasury` proposal.
+    DepositToTreasury {
+        funds: Vec<Coin>,
+    },
+    /// Called by gov to have `delegate` vote for this chain when it doesn't vote itself.
+    /// Limited to proposals with these actions, see [`crate::state::ProposalAction::name`]. All if None.
+    Delegate {
+        delegate: ChainName,
+        actions: Option<Vec<String>>,
+    },
+    /// Called by gov to drop the delegation of this chain
+    Undelegate {},
 }
 
 #[cosmwasm_schema::cw_serde]
-pub struct InterchainGovMigrateMsg {}
+pub struct InterchainGovMigrateMsg {
+    /// Proxy of the governed account, required when migrating from a version without one
+    pub account: Option<String>,
+}
+
+/// Packet sent between the governance adapters
+#[cosmwasm_schema::cw_serde]
+pub struct InterchainGovIbcPacket {
+    /// Adapter version of the sender, packets of incompatible versions are rejected
+    pub version: String,
+    /// State hash of the sender that the message is built on
+    pub parent_hash: Binary,
+    pub msg: InterchainGovIbcMsg,
+}
+
+impl InterchainGovIbcPacket {
+    pub fn new(parent_hash: Binary, msg: InterchainGovIbcMsg) -> Self {
+        InterchainGovIbcPacket {
+            version: ADAPTER_VERSION.to_string(),
+            parent_hash,
+            msg,
+        }
+    }
+
+    /// Bundle the messages in a single packet, applied in order and atomically by the receiver
+    pub fn batch(parent_hash: Binary, msgs: Vec<InterchainGovIbcMsg>) -> Self {
+        InterchainGovIbcPacket::new(parent_hash, InterchainGovIbcMsg::Batch { msgs })
+    }
+}
 
 #[cosmwasm_schema::cw_serde]
 pub enum InterchainGovIbcMsg {
@@ -76,6 +157,9 @@ pub enum InterchainGovIbcMsg {
     JoinGov {
         // All the members in the governance (including this chain)
         members: Members,
+        /// Chains that are invited, the members change once each of them responded
+        #[serde(default)]
+        invitees: Vec<ChainName>,
     },
     ProposeProposal {
         prop_hash: String,
@@ -86,11 +170,56 @@ pub enum InterchainGovIbcMsg {
     FinalizeProposal {
         prop_hash: String,
     },
+    /// A member refused the proposal, drop it
+    AbortProposal {
+        prop_hash: String,
+    },
     /// Sends the proposal results to the other chains.
     ProposalResult {
         prop_hash: String,
         outcome: ProposalOutcome,
     },
+    /// Answer of an invited chain to a `JoinGov` invite
+    InviteResponse {
+        accepted: bool,
+    },
+    /// Called when the sending chain leaves the Government
+    LeaveGov {},
+    /// Called when this chain was removed from the Government by a proposal
+    RemovedFromGov {
+        prop_hash: String,
+        /// Outcome of the proposal, the removed chain doesn't get the `ProposalResult`
+        outcome: ProposalOutcome,
+    },
+    /// Result of executing the action of a proposal, reported by the chain executing it
+    ActionResult {
+        prop_hash: String,
+        result: ActionResult,
+        /// Funds of the action that didn't arrive and went back to the treasury
+        #[serde(default)]
+        refunded: Vec<Coin>,
+    },
+    /// The sending chain pledged funds to the treasury
+    TreasuryDeposit {
+        funds: Vec<Coin>,
+    },
+    /// Veto of a guardian of the sending chain, for a proposal created by the receiving chain
+    VetoProposal {
+        prop_hash: String,
+        veto: Veto,
+    },
+    /// The sending chain changed its vote delegation, None if it dropped it
+    DelegationChanged {
+        delegation: Option<Delegation>,
+    },
+    /// A guardian of the sending chain paused the governance
+    Paused {
+        pause: Pause,
+    },
+    /// Several messages sent in one packet, see [`InterchainGovIbcPacket::batch`]
+    Batch {
+        msgs: Vec<InterchainGovIbcMsg>,
+    },
 }
 
 #[non_exhaustive]
@@ -103,6 +232,10 @@ pub enum InterchainGovIbcCallbackMsg {
         prop_hash: String,
         proposed_to: ChainName,
     },
+    AbortProposal {
+        prop_hash: String,
+        proposed_to: ChainName,
+    },
     ProposeProposal {
         prop_hash: String,
         proposed_to: ChainName,
@@ -111,6 +244,13 @@ pub enum InterchainGovIbcCallbackMsg {
         prop_hash: String,
         proposed_to: ChainName,
     },
+    SyncCheck {
+        chain: ChainName,
+    },
+    /// Callbacks of the messages of a batch, see [`InterchainGovIbcPacket::batch`]
+    Batch {
+        callbacks: Vec<InterchainGovIbcCallbackMsg>,
+    },
 }
 
 /// App query messages
@@ -122,6 +262,12 @@ pub enum InterchainGovQueryMsg {
     Config {},
     #[returns(MembersResponse)]
     Members {},
+    /// Members of the governance at a past membership epoch
+    #[returns(MembersResponse)]
+    MembersAt { epoch: u64 },
+    /// Invites that are waiting on our governance to accept or reject them
+    #[returns(PendingInvitesResponse)]
+    PendingInvites {},
     // #[returns(PendingProposalStates)]
     // PendingProposals {},
     #[returns(ProposalsResponse)]
@@ -135,6 +281,12 @@ pub enum InterchainGovQueryMsg {
     Proposal { prop_id: ProposalId },
     #[returns(Option<MapState>)]
     ProposalState { prop_id: ProposalId },
+    /// Last sync state transitions of the proposal
+    #[returns(ProposalHistoryResponse)]
+    ProposalHistory { prop_id: ProposalId },
+    /// Deposit held for a proposal created on this chain
+    #[returns(Option<Deposit>)]
+    Deposit { prop_id: ProposalId },
     /// Get the local vote
     #[returns(VoteResponse)]
     Vote {
@@ -144,6 +296,27 @@ pub enum InterchainGovQueryMsg {
 
     #[returns(VoteResultsResponse)]
     VoteResults { prop_id: ProposalId },
+    /// Ids of the synced and executed proposals, used to compare state between members
+    #[returns(ProposalHashesResponse)]
+    ProposalHashes {},
+    /// Hash committing to the finalized governance state
+    #[returns(StateHashResponse)]
+    StateHash {},
+    /// Members whose state disagrees with ours, or hasn't been reported yet, since the last `SyncCheck`
+    #[returns(SyncReportsResponse)]
+    SyncReports {},
+    /// Funds available in the shared treasury, per member
+    #[returns(TreasuryResponse)]
+    Treasury {},
+    /// Passed proposals of this chain waiting on their timelock
+    #[returns(QueuedProposalsResponse)]
+    QueuedProposals {},
+    /// Whether the governance is paused
+    #[returns(StatusResponse)]
+    Status {},
+    /// Vote delegations of the members
+    #[returns(DelegationsResponse)]
+    Delegations {},
 }
 
 /// App sudo messages
@@ -151,14 +324,105 @@ pub enum InterchainGovQueryMsg {
 pub enum InterchainGovSudoMsg {
     #[serde(rename = "kv_query_result")]
     KVQueryResult { query_id: u64 },
+    /// Ack of an ICS-20 transfer sent from the treasury
+    Response {
+        request: RequestPacket,
+        data: Binary,
+    },
+    /// Error ack of an ICS-20 transfer sent from the treasury, its funds were refunded
+    Error {
+        request: RequestPacket,
+        details: String,
+    },
+    /// Timeout of an ICS-20 transfer sent from the treasury, its funds were refunded
+    Timeout { request: RequestPacket },
+}
+
+/// Packet of an ack or timeout, in the format of Neutron's sudo callbacks
+#[cosmwasm_schema::cw_serde]
+pub struct RequestPacket {
+    pub sequence: Option<u64>,
+    pub source_port: Option<String>,
+    pub source_channel: Option<String>,
+    pub destination_port: Option<String>,
+    pub destination_channel: Option<String>,
+    pub data: Option<Binary>,
+    pub timeout_height: Option<RequestPacketTimeoutHeight>,
+    pub timeout_timestamp: Option<u64>,
 }
 
 #[cosmwasm_schema::cw_serde]
-pub struct ConfigResponse {}
+pub struct RequestPacketTimeoutHeight {
+    pub revision_number: Option<u64>,
+    pub revision_height: Option<u64>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct ConfigResponse {
+    pub config: GovConfig,
+    /// Config of a passed `UpdateConfig` proposal that is waiting on the other members' acks
+    pub pending: Option<GovConfig>,
+}
 
 #[cosmwasm_schema::cw_serde]
 pub struct MembersResponse {
     pub members: Members,
+    /// Membership epoch of these members
+    pub epoch: u64,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct ProposalHashesResponse {
+    /// Proposals in the synced proposal map
+    pub proposals: Vec<ProposalId>,
+    /// Proposals that have an outcome
+    pub executed: Vec<ProposalId>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct StateHashResponse {
+    pub hash: Binary,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct SyncReportsResponse {
+    /// Chains that reported a state that differs from ours
+    pub diverged: Vec<(ChainName, SyncReport)>,
+    /// Chains that didn't answer the sync check yet
+    pub pending: Vec<ChainName>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct TreasuryResponse {
+    pub balances: Vec<(ChainName, Vec<Coin>)>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct DelegationsResponse {
+    /// Delegating chain and its delegation
+    pub delegations: Vec<(ChainName, Delegation)>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct StatusResponse {
+    /// Set while the governance is paused
+    pub pause: Option<Pause>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct QueuedProposalsResponse {
+    pub proposals: Vec<(ProposalId, QueuedProposal)>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct ProposalHistoryResponse {
+    /// Oldest first
+    pub events: Vec<SyncEvent>,
+}
+
+#[cosmwasm_schema::cw_serde]
+pub struct PendingInvitesResponse {
+    pub invites: Vec<(ChainName, Invite)>,
 }
 
 #[cosmwasm_schema::cw_serde]
@@ -191,12 +455,14 @@ pub struct VoteResponse {
     // TODO: maybe this field should not be included
     pub prop_id: ProposalId,
     pub chain: ChainName,
-    pub governance: Governance,
-    pub vote: Vote,
+    /// None if the chain didn't vote
+    pub vote: Option<GovernanceVote>,
 }
 
 #[cosmwasm_schema::cw_serde]
 pub struct VoteResultsResponse {
     pub prop_id: ProposalId,
-    pub results: Vec<(ChainName, Option<GovernanceVote>)>,
+    pub results: Vec<(ChainName, VoteResult)>,
+    /// Votes of the members as they count, direct or delegated. Pending results are left out.
+    pub counted: Vec<CountedVote>,
 }
@@ -0,0 +1,61 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::Storage;
+
+use crate::contract::AdapterResult;
+use crate::state::{
+    load_gov_config, Authority, Guardian, Pause, Proposal, ProposalAction, ProposalId, PAUSE,
+};
+use crate::InterchainGovError;
+
+/// Error if the governance is paused, unless every action `prop` can execute lifts the pause.
+/// For multiple choice proposals these are the actions of its options.
+pub(crate) fn assert_not_paused(
+    storage: &dyn Storage,
+    prop: Option<&Proposal>,
+) -> AdapterResult<()> {
+    let lifts_pause = prop.is_some_and(|prop| {
+        let actions = prop.actions();
+        !actions.is_empty()
+            && actions
+                .iter()
+                .all(|action| matches!(action, ProposalAction::Unpause {}))
+    });
+    match PAUSE.may_load(storage)? {
+        Some(_) if lifts_pause => Ok(()),
+        Some(Pause { reason, .. }) => Err(InterchainGovError::Paused { reason }),
+        None => Ok(()),
+    }
+}
+
+/// Apply the `Pause` and `Unpause` actions of passed proposals
+pub(crate) fn apply_pause_action(
+    storage: &mut dyn Storage,
+    prop_id: &ProposalId,
+    action: &ProposalAction,
+) -> AdapterResult<()> {
+    match action {
+        ProposalAction::Pause { reason } => PAUSE.save(
+            storage,
+            &Pause {
+                by: Authority::Proposal(prop_id.clone()),
+                reason: reason.clone(),
+            },
+        )?,
+        ProposalAction::Unpause {} => PAUSE.remove(storage),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Guardian of `chain` with `address`, as set in the config
+pub(crate) fn load_guardian(
+    storage: &dyn Storage,
+    chain: &ChainName,
+    address: &str,
+) -> AdapterResult<Guardian> {
+    load_gov_config(storage)?
+        .guardians
+        .find(chain, address)
+        .cloned()
+        .ok_or(InterchainGovError::Unauthorized {})
+}
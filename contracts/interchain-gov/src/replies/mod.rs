@@ -1,3 +1,7 @@
 mod icq;
+mod transfer;
 
-pub use self::icq::icq_reply;
+/// Reply to the ICS-20 transfers sent from the treasury, the ICQ replies use the ids below
+pub const TRANSFER_REPLY_ID: u64 = 7;
+
+pub use self::{icq::icq_reply, transfer::transfer_reply};
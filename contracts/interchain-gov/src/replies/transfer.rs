@@ -0,0 +1,63 @@
+use crate::contract::{AdapterResult, InterchainGov};
+use crate::state::{ActionResult, FINALIZED_PROPOSALS, PENDING_TRANSFERS, TRANSFER_PACKETS};
+use crate::treasury::settle_transfer;
+
+use abstract_adapter::sdk::AbstractResponse;
+use cosmwasm_std::{DepsMut, Env, Event, Reply, StdError, StdResult};
+
+/// Event of the IBC module for a sent packet
+const SEND_PACKET_EVENT: &str = "send_packet";
+
+/// Track an ICS-20 transfer sent from the treasury until its ack or timeout, or settle it as
+/// failed if it couldn't be sent
+pub fn transfer_reply(deps: DepsMut, env: Env, app: InterchainGov, reply: Reply) -> AdapterResult {
+    let (prop_id, coin) = PENDING_TRANSFERS
+        .pop_front(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("no pending transfer"))?;
+
+    match reply.result.into_result() {
+        Ok(res) => {
+            let (channel, sequence) = sent_packet(&res.events)?;
+            TRANSFER_PACKETS.save(deps.storage, (&channel, sequence), &(prop_id.clone(), coin))?;
+
+            let (prop, mut outcome) = FINALIZED_PROPOSALS.load(deps.storage, prop_id.clone())?;
+            if outcome.action_result.is_none() {
+                outcome.action_result = Some(ActionResult::Pending);
+                FINALIZED_PROPOSALS.save(deps.storage, prop_id.clone(), &(prop, outcome))?;
+            }
+            Ok(app
+                .response("transfer_reply")
+                .add_attribute("prop_id", prop_id)
+                .add_attribute("executed", "pending")
+                .add_attribute("sequence", sequence.to_string()))
+        }
+        Err(error) => {
+            let msgs = settle_transfer(deps, &env, &app, &prop_id, coin, Some(error))?;
+            Ok(app
+                .response("transfer_reply")
+                .add_messages(msgs)
+                .add_attribute("prop_id", prop_id)
+                .add_attribute("executed", "false"))
+        }
+    }
+}
+
+/// Source channel and sequence of the packet sent by the transfer
+fn sent_packet(events: &[Event]) -> StdResult<(String, u64)> {
+    let attribute = |event: &Event, key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+    };
+    events
+        .iter()
+        .filter(|event| event.ty == SEND_PACKET_EVENT)
+        .find_map(|event| {
+            let channel = attribute(event, "packet_src_channel")?;
+            let sequence = attribute(event, "packet_sequence")?.parse().ok()?;
+            Some((channel, sequence))
+        })
+        .ok_or_else(|| StdError::generic_err("transfer didn't send a packet"))
+}
@@ -3,13 +3,19 @@ use std::fmt::Display;
 use abstract_adapter::objects::chain_name::ChainName;
 use base64::Engine;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, Env, Uint128};
-use cw_storage_plus::{Item, Map};
-use cw_utils::Expiration;
+use cosmwasm_std::{
+    to_json_string, Addr, Api, Binary, BlockInfo, Coin, Decimal, Env, StdResult, Storage, Uint128,
+};
+use cw_asset::{Asset, AssetInfo, AssetUnchecked};
+use cw_storage_plus::{Deque, Item, Map};
+use cw_utils::{Duration, Expiration};
 use dao_voting::threshold::{PercentageThreshold, Threshold};
-use ibc_sync_state::MapStateSyncController;
+use dao_voting::voting::does_vote_count_pass;
+use ibc_sync_state::{ItemStateSyncController, MapStateSyncController, TwoPhaseCommit};
 use members_sync_state::MembersSyncState;
 
+use crate::InterchainGovError;
+
 pub type ProposalId = String;
 pub type StorageKey = String;
 pub type Key = String;
@@ -28,17 +34,25 @@ pub type Key = String;
 pub const MEMBERS_KEY: &str = "members";
 pub const MEMBERS: Item<Members> = Item::new(MEMBERS_KEY);
 pub const MEMBERS_STATE_SYNC: MembersSyncState = MembersSyncState::new();
-pub const OUTSTANDING_ACKS: Item<Vec<ChainName>> = Item::new("acks");
+/// Membership epoch, incremented whenever the members change
+pub const MEMBERSHIP_EPOCH: Item<u64> = Item::new("epoch");
+/// Members of the governance at each epoch
+pub const MEMBERS_AT: Map<u64, Members> = Map::new("members_at");
 pub const ALLOW_JOINING_GOV: Item<Members> = Item::new("alw");
+/// Invites to join a governance, keyed by the inviting chain. Awaiting acceptance by our governance.
+pub const PENDING_INVITES: Map<&ChainName, Invite> = Map::new("invites");
+/// Running hash over the finalized governance state, see [`StateCommit`]
+pub const STATE_HASH: Item<Binary> = Item::new("state_hash");
+/// Result of the last sync check for each member
+pub const SYNC_REPORTS: Map<&ChainName, SyncReport> = Map::new("sync_reports");
 
 // TODO: should we actually have these as separate maps?
 
 pub const VOTE: Map<ProposalId, GovernanceVote> = Map::new("vote");
 pub const VOTES: Map<ProposalId, (ChainName, GovernanceVote)> = Map::new("votes");
 
-/// Remote vote results, None = requested
-pub const VOTE_RESULTS: Map<(ProposalId, &ChainName), Option<GovernanceVote>> =
-    Map::new("vote_results");
+/// Remote vote results, pending once requested
+pub const VOTE_RESULTS: Map<(ProposalId, &ChainName), VoteResult> = Map::new("vote_results");
 /// Pending vote queries
 pub const GOV_VOTE_QUERIES: Map<(ProposalId, &ChainName), Option<TallyResult>> =
     Map::new("pending_queries");
@@ -48,46 +62,134 @@ pub const TEMP_REMOTE_GOV_MODULE_ADDRS: Map<&ChainName, String> =
 /// Map queryid -> (chain, prop_id)
 pub const PENDING_REPLIES: Map<u64, (ChainName, ProposalId)> = Map::new("pending_replies");
 /// Map queryid -> chain
-pub const PENDING_QUERIES: Map<u64, (ChainName, ProposalId)> = Map::new("pending_replies");
+pub const PENDING_QUERIES: Map<u64, (ChainName, ProposalId)> = Map::new("pending_icqs");
 const PROPOSALS: Map<ProposalId, (Proposal, Vote)> = Map::new("props");
+/// Number of sync events kept per proposal
+pub const PROPOSAL_HISTORY_LIMIT: u32 = 10;
 pub const PROPOSAL_STATE_SYNC: MapStateSyncController<'_, ProposalId, (Proposal, Vote)> =
-    MapStateSyncController::new(PROPOSALS);
+    MapStateSyncController::new(PROPOSALS).with_history("prop_history", PROPOSAL_HISTORY_LIMIT);
+/// Proposals are synced with a two-phase commit, the other members are its participants
+pub const PROPOSAL_TWO_PHASE: TwoPhaseCommit<'_, ProposalId, (Proposal, Vote)> =
+    TwoPhaseCommit::new(PROPOSAL_STATE_SYNC);
 
 pub const FINALIZED_PROPOSALS: Map<ProposalId, (Proposal, ProposalOutcome)> =
     Map::new("finalized_props");
 
+pub const GOV_CONFIG: Item<GovConfig> = Item::new("gov_config");
+/// Governance parameters, changed mesh-wide by `UpdateConfig` proposals
+pub const GOV_CONFIG_SYNC: ItemStateSyncController<'_, GovConfig> =
+    ItemStateSyncController::new(GOV_CONFIG);
+
+/// Finalized governance parameters, the defaults if never set
+pub fn load_gov_config(storage: &dyn Storage) -> StdResult<GovConfig> {
+    Ok(GOV_CONFIG.may_load(storage)?.unwrap_or_default())
+}
+/// Deposits held for the proposals created on this chain
+pub const DEPOSITS: Map<ProposalId, Deposit> = Map::new("deposits");
+
+/// Treasury ledger, funds pledged by each member per denom
+pub const TREASURY: Map<(&ChainName, &str), TreasuryBalance> = Map::new("treasury");
+/// Proxy of the account governed by this adapter, set at instantiate.
+/// It holds this chain's treasury funds.
+pub const GOVERNING_ACCOUNT: Item<Addr> = Item::new("governing_account");
+/// ICS-20 transfers awaiting their reply, in the order they were sent
+pub const PENDING_TRANSFERS: Deque<(ProposalId, Coin)> = Deque::new("pending_transfers");
+/// Sent ICS-20 transfers awaiting their ack or timeout, by source channel and sequence
+pub const TRANSFER_PACKETS: Map<(&str, u64), (ProposalId, Coin)> = Map::new("transfer_packets");
+/// Passed proposals of this chain waiting on their timelock, executed by `ExecuteQueued`
+pub const QUEUED_PROPOSALS: Map<ProposalId, QueuedProposal> = Map::new("queued_props");
+/// Set while the governance is paused, by a guardian or a `Pause` proposal
+pub const PAUSE: Item<Pause> = Item::new("pause");
+/// Vote delegations of the members, keyed by the delegating chain
+pub const DELEGATIONS: Map<&ChainName, Delegation> = Map::new("delegations");
+
+// Storage layout must not overlap, including the namespaces of the sync controllers
+const _: () = ibc_sync_state::assert_unique_namespaces(&[
+    MEMBERS_KEY,
+    "epoch",
+    "members_at",
+    "alw",
+    "invites",
+    "state_hash",
+    "sync_reports",
+    "vote",
+    "votes",
+    "vote_results",
+    "pending_queries",
+    "temp_remote_gov_module_addrs",
+    "pending_replies",
+    "pending_icqs",
+    "props",
+    "finalized_props",
+    "prop_history",
+    "gov_config",
+    "deposits",
+    "treasury",
+    "governing_account",
+    "pending_transfers",
+    "transfer_packets",
+    "queued_props",
+    "pause",
+    "delegations",
+    ibc_sync_state::MAP_DATA_NAMESPACE,
+    ibc_sync_state::MAP_ACKS_NAMESPACE,
+    ibc_sync_state::MAP_BATCHES_NAMESPACE,
+    ibc_sync_state::ITEM_DATA_NAMESPACE,
+    ibc_sync_state::ITEM_ACKS_NAMESPACE,
+    ibc_sync_state::TWO_PHASE_NAMESPACE,
+]);
+
 /// Local members to local data status
 /// Remote member statuses
 
 pub mod members_sync_state {
     use abstract_adapter::objects::chain_name::ChainName;
-    use cosmwasm_std::{from_json, to_json_binary, Env, StdResult, Storage};
-    use cw_storage_plus::Item;
-    use ibc_sync_state::{ItemStateSyncController, StateChange, SyncStateError, SyncStateResult};
+    use cosmwasm_std::{Env, StdResult, Storage};
+    use ibc_sync_state::{ItemStateSyncController, SyncEvent, SyncStateResult};
 
-    use super::{Members, MEMBERS_KEY};
+    use super::{Members, MEMBERSHIP_EPOCH, MEMBERS_AT};
 
     pub struct MembersSyncState {
-        item_state_controller: ItemStateSyncController,
-        members: Item<'static, Members>,
-        outstanding_acks: Item<'static, Vec<ChainName>>,
+        item_state_controller: ItemStateSyncController<'static, Members>,
     }
 
     impl MembersSyncState {
         pub const fn new() -> Self {
             MembersSyncState {
-                item_state_controller: ItemStateSyncController::new(),
-                members: super::MEMBERS,
-                outstanding_acks: super::OUTSTANDING_ACKS,
+                item_state_controller: ItemStateSyncController::new(super::MEMBERS),
             }
         }
 
         pub fn load_members(&self, storage: &dyn Storage) -> StdResult<Members> {
-            self.members.load(storage)
+            self.item_state_controller.item().load(storage)
         }
 
+        /// Save the members, starting a new membership epoch if they changed
         pub fn save_members(&self, storage: &mut dyn Storage, members: &Members) -> StdResult<()> {
-            self.members.save(storage, members)
+            let previous = self.item_state_controller.item().may_load(storage)?;
+            self.item_state_controller.item().save(storage, members)?;
+            self.start_epoch(storage, previous, members)
+        }
+
+        fn start_epoch(
+            &self,
+            storage: &mut dyn Storage,
+            previous: Option<Members>,
+            members: &Members,
+        ) -> StdResult<()> {
+            if previous.as_ref() == Some(members) {
+                return Ok(());
+            }
+            let epoch = match MEMBERSHIP_EPOCH.may_load(storage)? {
+                Some(epoch) => epoch + 1,
+                None => 0,
+            };
+            MEMBERSHIP_EPOCH.save(storage, &epoch)?;
+            MEMBERS_AT.save(storage, epoch, members)
+        }
+
+        pub fn load_epoch(&self, storage: &dyn Storage) -> StdResult<u64> {
+            Ok(MEMBERSHIP_EPOCH.may_load(storage)?.unwrap_or_default())
         }
 
         pub fn external_members(
@@ -100,62 +202,36 @@ pub mod members_sync_state {
             Ok(members)
         }
 
-        pub fn load_state_change(&self, storage: &dyn Storage) -> SyncStateResult<StateChange> {
-            self.item_state_controller
-                .load_state_change(storage, MEMBERS_KEY)
-        }
-
-        // Instantiate state change and register outstanding receipts
+        /// Instantiate a members change that waits on the acks of `acks`
         pub fn initiate_members(
             &self,
             storage: &mut dyn Storage,
-            env: &Env,
             members: Members,
-        ) -> SyncStateResult<()> {
+            acks: Vec<ChainName>,
+        ) -> SyncStateResult<SyncEvent> {
             self.item_state_controller
-                .assert_finalized(storage, MEMBERS_KEY.to_string())?;
-            self.item_state_controller.initiate_item_state(
-                storage,
-                MEMBERS_KEY.to_string(),
-                StateChange::Proposal(to_json_binary(&members)?),
-            )?;
-            let members = self.external_members(storage, env)?;
-            self.outstanding_acks.save(storage, &members.members)?;
-            Ok(())
+                .initiate_item_state(storage, members, acks)
         }
 
         pub fn apply_ack(
             &self,
             storage: &mut dyn Storage,
             chain: ChainName,
-        ) -> SyncStateResult<Option<ChainName>> {
-            let mut acks = self.outstanding_acks.load(storage)?;
-            // find chain in acks and remove it
-            let receipt_i = acks.iter().position(|c| c == &chain);
-            let ack_chain = match receipt_i {
-                Some(receipt_i) => acks.remove(receipt_i),
-                None => return Ok(None),
-            };
-
-            self.outstanding_acks.save(storage, &acks)?;
-            Ok(Some(ack_chain))
+        ) -> SyncStateResult<Option<SyncEvent>> {
+            self.item_state_controller.apply_ack(storage, chain)
         }
 
         pub fn has_outstanding_acks(&self, storage: &dyn Storage) -> StdResult<bool> {
-            let acks = self.outstanding_acks.load(storage)?;
-            Ok(!acks.is_empty())
+            self.item_state_controller.has_outstanding_acks(storage)
         }
 
         pub fn propose_members(
             &self,
             storage: &mut dyn Storage,
             members: Members,
-        ) -> SyncStateResult<()> {
-            self.item_state_controller.propose_item_state(
-                storage,
-                MEMBERS_KEY.to_string(),
-                StateChange::Proposal(to_json_binary(&members)?),
-            )
+        ) -> SyncStateResult<SyncEvent> {
+            self.item_state_controller
+                .propose_item_state(storage, members)
         }
 
         pub fn finalize_members(
@@ -164,48 +240,39 @@ pub mod members_sync_state {
             // Members to finalize
             // Uses initialized / proposed state None
             members: Option<Members>,
-        ) -> SyncStateResult<()> {
-            let (members, set) = {
-                if let Some(members) = members {
-                    (members, true)
-                } else {
-                    let members: Result<Members, _> = match self.load_state_change(storage)? {
-                        StateChange::Proposal(members) => Ok(from_json(members)?),
-                        _ => Err(SyncStateError::DataNotFinalized {
-                            key: MEMBERS_KEY.to_string(),
-                            state: "Backup".to_string(),
-                        }),
-                    };
-                    (members?, false)
-                }
-            };
-
-            self.members.save(storage, &members)?;
-
-            self.item_state_controller.finalize_item_state(
-                storage,
-                MEMBERS_KEY.to_string(),
-                set,
-            )?;
-            Ok(())
+        ) -> SyncStateResult<SyncEvent> {
+            let previous = self.item_state_controller.may_load(storage)?;
+            let event = self
+                .item_state_controller
+                .finalize_item_state(storage, members)?;
+            let members = self.load_members(storage)?;
+            self.start_epoch(storage, previous, &members)?;
+            Ok(event)
         }
 
         pub fn assert_finalized(&self, storage: &dyn Storage) -> SyncStateResult<()> {
-            self.item_state_controller
-                .assert_finalized(storage, MEMBERS_KEY.to_string())
+            self.item_state_controller.assert_finalized(storage)
         }
 
-        // pub fn assert_proposed(&self, storage: &dyn Storage) -> SyncStateResult<()> {
-        //     self.item_state_controller.assert_proposed(storage, MEMBERS_KEY.to_string())
-        // }
+        /// Remove a member from the pending members change, used when an invite is rejected.
+        pub fn drop_proposed_member(
+            &self,
+            storage: &mut dyn Storage,
+            member: &ChainName,
+        ) -> SyncStateResult<SyncEvent> {
+            let mut members = self.item_state_controller.load_pending(storage)?;
+            members.members.retain(|m| m != member);
 
-        // pub fn assert_initiated(&self, storage: &dyn Storage) -> SyncStateResult<()> {
-        //     self.item_state_controller.assert_initiated(storage, MEMBERS_KEY.to_string())
-        // }
+            let acks = self.item_state_controller.outstanding_acks(storage)?;
+            self.item_state_controller.clear_state(storage);
+            self.item_state_controller
+                .initiate_item_state(storage, members, acks)
+        }
     }
 }
 
 #[cw_serde]
+#[derive(Default)]
 pub struct Members {
     pub members: Vec<ChainName>,
 }
@@ -218,6 +285,100 @@ impl Members {
     }
 }
 
+/// Invite to join a governance
+#[cw_serde]
+pub struct Invite {
+    /// All the members of the governance, including the invited chain
+    pub members: Members,
+    /// State hash of the governance, adopted when accepting the invite
+    pub parent_hash: Binary,
+    /// Chains invited together with this one, including it
+    #[serde(default)]
+    pub invitees: Vec<ChainName>,
+    /// Responses of the other invitees that arrived before this invite was answered
+    #[serde(default)]
+    pub responses: Vec<(ChainName, bool)>,
+}
+
+/// Change to the governance state that is committed to the [`STATE_HASH`].
+/// Every member commits the same changes in the same order, so their hashes stay equal.
+#[cw_serde]
+pub enum StateCommit {
+    /// Proposal is synced with all members
+    Proposal {
+        prop_id: ProposalId,
+        prop: Box<Proposal>,
+    },
+    /// Proposal got an outcome
+    Outcome {
+        prop_id: ProposalId,
+        outcome: ProposalOutcome,
+    },
+    /// Members of the governance changed, committed with the resulting members
+    Members { members: Members },
+}
+
+/// Difference between a remote set and the local one
+#[cw_serde]
+pub struct StateDiff<T> {
+    /// Present locally, absent on the remote chain
+    pub missing: Vec<T>,
+    /// Present on the remote chain, absent locally
+    pub unexpected: Vec<T>,
+}
+
+impl<T: PartialEq + Clone> StateDiff<T> {
+    pub fn new(local: &[T], remote: &[T]) -> Self {
+        StateDiff {
+            missing: local
+                .iter()
+                .filter(|i| !remote.contains(i))
+                .cloned()
+                .collect(),
+            unexpected: remote
+                .iter()
+                .filter(|i| !local.contains(i))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Comparison of a member's state with our own. `None` fields are awaiting the member's answer.
+#[cw_serde]
+pub struct SyncReport {
+    /// Height at which the check was requested
+    pub requested_at: u64,
+    pub members: Option<StateDiff<ChainName>>,
+    pub proposals: Option<StateDiff<ProposalId>>,
+    pub executed: Option<StateDiff<ProposalId>>,
+}
+
+impl SyncReport {
+    pub fn new(requested_at: u64) -> Self {
+        SyncReport {
+            requested_at,
+            members: None,
+            proposals: None,
+            executed: None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.members.is_none() || self.proposals.is_none() || self.executed.is_none()
+    }
+
+    pub fn is_diverged(&self) -> bool {
+        self.members.as_ref().is_some_and(|d| !d.is_empty())
+            || self.proposals.as_ref().is_some_and(|d| !d.is_empty())
+            || self.executed.as_ref().is_some_and(|d| !d.is_empty())
+    }
+}
+
 impl From<Vec<ChainName>> for Members {
     fn from(members: Vec<ChainName>) -> Self {
         Members { members }
@@ -228,7 +389,77 @@ impl From<Vec<ChainName>> for Members {
 #[cw_serde]
 pub enum ProposalAction {
     Signal,
-    UpdateMembers { members: Members },
+    UpdateMembers {
+        members: Members,
+    },
+    /// Remove a member from the governance. The removed chain is notified over IBC.
+    RemoveMember {
+        member: ChainName,
+    },
+    /// Send funds from the treasury of a member, executed by that member's governing account
+    SpendFromTreasury {
+        chain: ChainName,
+        /// Address on `chain`
+        recipient: String,
+        amount: Vec<Coin>,
+    },
+    /// ICS-20 transfer from the treasury of `from_chain` to a recipient on `to_chain`.
+    /// The result is reported back to the proposing chain.
+    /// Only Neutron chains report the result of a transfer to the adapter, so only they can send.
+    IbcTransfer {
+        from_chain: ChainName,
+        to_chain: ChainName,
+        /// Address on `to_chain`
+        recipient: String,
+        amount: Vec<Coin>,
+    },
+    /// Replace the governance parameters on every member
+    UpdateConfig {
+        config: GovConfig,
+    },
+    /// Replace the guardian set of the governance
+    UpdateGuardians {
+        guardians: Guardians,
+    },
+    /// Veto a proposal during its timelock, like a guardian would
+    VetoProposal {
+        prop_id: ProposalId,
+        reason: String,
+    },
+    /// Pause the governance of every member
+    Pause {
+        reason: String,
+    },
+    /// Lift the pause, the only proposals that progress while paused
+    Unpause {},
+}
+
+impl ProposalAction {
+    /// Name of the action, as listed in [`GovConfig::allowed_actions`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProposalAction::Signal => "signal",
+            ProposalAction::UpdateMembers { .. } => "update_members",
+            ProposalAction::RemoveMember { .. } => "remove_member",
+            ProposalAction::SpendFromTreasury { .. } => "spend_from_treasury",
+            ProposalAction::IbcTransfer { .. } => "ibc_transfer",
+            ProposalAction::UpdateConfig { .. } => "update_config",
+            ProposalAction::UpdateGuardians { .. } => "update_guardians",
+            ProposalAction::VetoProposal { .. } => "veto_proposal",
+            ProposalAction::Pause { .. } => "pause",
+            ProposalAction::Unpause {} => "unpause",
+        }
+    }
+
+    /// Whether emergency proposals can take the action
+    pub fn allows_emergency(&self) -> bool {
+        matches!(
+            self,
+            ProposalAction::UpdateGuardians { .. }
+                | ProposalAction::VetoProposal { .. }
+                | ProposalAction::Pause { .. }
+        )
+    }
 }
 
 #[cw_serde]
@@ -246,11 +477,91 @@ pub struct ProposalMsg {
     pub expiration: Expiration,
     /// A standard action that the group can run
     pub action: ProposalAction,
+    /// Pass with [`GovConfig::emergency_threshold`] and execute as soon as it's reached.
+    /// Only for the actions of [`ProposalAction::allows_emergency`].
+    #[serde(default)]
+    pub emergency: bool,
+    /// Options to vote on instead of yes/no, `action` has to be [`ProposalAction::Signal`] then
+    #[serde(default)]
+    pub choices: Option<Choices>,
+}
+
+/// Options of a multiple choice proposal
+#[cw_serde]
+pub struct Choices {
+    pub options: Vec<ProposalOption>,
+    pub rule: ChoiceRule,
+}
+
+#[cw_serde]
+pub struct ProposalOption {
+    pub title: String,
+    /// Executed if the option wins
+    pub action: Option<ProposalAction>,
+}
+
+/// How the winning option is picked
+#[cw_serde]
+pub enum ChoiceRule {
+    /// The option with the most votes, ties have no winner
+    Plurality,
+    /// The option with the most votes, if they reach the share of all members
+    Threshold { percentage: PercentageThreshold },
+}
+
+impl Choices {
+    /// Tally the votes of the members, votes that aren't for an option count as not voting
+    pub fn tally(&self, votes: &[Vote]) -> ProposalOutcome {
+        let mut tallies = vec![0u32; self.options.len()];
+        let mut no_votes = 0;
+        for vote in votes {
+            match vote {
+                Vote::Choice(index) if (*index as usize) < tallies.len() => {
+                    tallies[*index as usize] += 1
+                }
+                _ => no_votes += 1,
+            }
+        }
+
+        let max = tallies.iter().copied().max().unwrap_or_default();
+        let leaders = tallies.iter().filter(|tally| **tally == max).count();
+        let members = Uint128::from(votes.len() as u128);
+        let winner = tallies
+            .iter()
+            .position(|tally| *tally == max)
+            .filter(|_| max > 0 && leaders == 1)
+            .filter(|_| match self.rule {
+                ChoiceRule::Plurality => true,
+                ChoiceRule::Threshold { percentage } => {
+                    does_vote_count_pass(Uint128::from(max), members, percentage)
+                }
+            });
+        let votes_for = winner.map(|_| max).unwrap_or_default();
+
+        ProposalOutcome {
+            passed: winner.is_some(),
+            votes_for,
+            votes_against: votes.len() as u32 - votes_for,
+            no_votes,
+            action_result: None,
+            vetoed: None,
+            tallies,
+            winner: winner.map(|index| index as u32),
+        }
+    }
 }
 
 impl Display for ProposalMsg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.title, self.description)
+        write!(f, "{}: {}", self.title, self.description)?;
+        // Proposals that only differ in what they execute are different proposals
+        let action = to_json_string(&self.action).map_err(|_| std::fmt::Error)?;
+        write!(f, " {action}")?;
+        if let Some(choices) = &self.choices {
+            let choices = to_json_string(choices).map_err(|_| std::fmt::Error)?;
+            write!(f, " {choices}")?;
+        }
+        Ok(())
     }
 }
 
@@ -283,33 +594,95 @@ pub struct Proposal {
     /// The the time at which this proposal will expire and close for
     /// additional votes.
     pub expiration: Expiration,
+    /// Block of the proposing chain when the proposal was created
+    #[serde(default)]
+    pub created: Option<BlockInfo>,
     /// The threshold at which this proposal will pass.
     pub threshold: Threshold,
+    /// Membership epoch of the proposing chain when the proposal was created
+    #[serde(default)]
+    pub epoch: u64,
+    /// Members that get to vote on this proposal, set by the migration for older proposals
+    #[serde(default)]
+    pub members: Members,
+    /// Can be executed before its expiration, once the threshold is reached
+    #[serde(default)]
+    pub emergency: bool,
+    /// Options voted on instead of yes/no
+    #[serde(default)]
+    pub choices: Option<Choices>,
     // /// The proposal status
     // pub status: Status,
 }
 
 impl Proposal {
-    pub fn new(proposal: ProposalMsg, proposer: &Addr, env: &Env) -> Self {
+    pub fn new(
+        proposal: ProposalMsg,
+        proposer: &Addr,
+        env: &Env,
+        epoch: u64,
+        members: Members,
+        config: &GovConfig,
+    ) -> Self {
         let ProposalMsg {
             title,
             description,
             min_voting_period,
             expiration,
             action,
+            emergency,
+            choices,
         } = proposal;
 
+        // Emergency proposals don't wait for a voting period
+        let (min_voting_period, threshold) = match config.emergency_threshold {
+            Some(percentage) if emergency => (None, Threshold::AbsolutePercentage { percentage }),
+            _ => (
+                min_voting_period
+                    .or_else(|| config.voting_period.map(|period| period.after(&env.block))),
+                config.threshold(),
+            ),
+        };
+
         Proposal {
             title,
             description,
             action,
             min_voting_period,
             expiration,
+            created: Some(env.block.clone()),
             proposer: proposer.to_string(),
             proposer_chain: ChainName::new(env),
-            threshold: Threshold::AbsolutePercentage {
-                percentage: PercentageThreshold::Percent(Decimal::percent(100)),
-            },
+            threshold,
+            epoch,
+            members,
+            emergency,
+            choices,
+        }
+    }
+
+    /// Action to execute for the outcome, the winning option's for multiple choice proposals
+    pub fn action_for(&self, outcome: &ProposalOutcome) -> ProposalAction {
+        match (&self.choices, outcome.winner) {
+            (None, _) => self.action.clone(),
+            (Some(choices), Some(winner)) => choices
+                .options
+                .get(winner as usize)
+                .and_then(|option| option.action.clone())
+                .unwrap_or(ProposalAction::Signal),
+            (Some(_), None) => ProposalAction::Signal,
+        }
+    }
+
+    /// Every action the proposal can execute
+    pub fn actions(&self) -> Vec<&ProposalAction> {
+        match &self.choices {
+            None => vec![&self.action],
+            Some(choices) => choices
+                .options
+                .iter()
+                .filter_map(|option| option.action.as_ref())
+                .collect(),
         }
     }
 }
@@ -320,6 +693,8 @@ pub enum Vote {
     Yes,
     No,
     NoVote,
+    /// Index of the selected option of a multiple choice proposal
+    Choice(u32),
 }
 
 #[non_exhaustive]
@@ -346,11 +721,338 @@ impl GovernanceVote {
         GovernanceVote { vote, governance }
     }
 }
+
+/// Vote result of another member
+#[cw_serde]
+pub enum VoteResult {
+    /// Requested, the member didn't answer yet
+    Pending,
+    /// The member didn't vote, its delegate's vote counts instead
+    NotCast,
+    Cast(GovernanceVote),
+}
+
+impl VoteResult {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, VoteResult::Pending)
+    }
+
+    /// Vote cast by the member, None if it didn't vote or answer yet
+    pub fn vote(&self) -> Option<Vote> {
+        match self {
+            VoteResult::Cast(vote) => Some(vote.vote.clone()),
+            _ => None,
+        }
+    }
+}
 #[cw_serde]
 pub struct ProposalOutcome {
     pub passed: bool,
-    pub votes_for: u8,
-    pub votes_against: u8,
+    pub votes_for: u32,
+    pub votes_against: u32,
+    /// Members that didn't vote, included in `votes_against`
+    #[serde(default)]
+    pub no_votes: u32,
+    /// Result of an action executed by another member, once it is reported back
+    #[serde(default)]
+    pub action_result: Option<ActionResult>,
+    /// Set if a guardian vetoed the proposal during its timelock, `passed` is false then
+    #[serde(default)]
+    pub vetoed: Option<Veto>,
+    /// Votes per option of a multiple choice proposal
+    #[serde(default)]
+    pub tallies: Vec<u32>,
+    /// Index of the winning option of a multiple choice proposal, `votes_for` are its votes
+    #[serde(default)]
+    pub winner: Option<u32>,
+}
+
+/// Veto of a passed proposal
+#[cw_serde]
+pub struct Veto {
+    pub by: Authority,
+    pub reason: String,
+}
+
+/// Origin of a veto or pause
+#[cw_serde]
+pub enum Authority {
+    Guardian(Guardian),
+    /// Emergency proposal with a [`ProposalAction::VetoProposal`] or [`ProposalAction::Pause`] action
+    Proposal(ProposalId),
+}
+
+/// Pause of the governance, proposals don't progress until an `Unpause` proposal passes
+#[cw_serde]
+pub struct Pause {
+    pub by: Authority,
+    pub reason: String,
+}
+
+/// Account allowed to veto passed proposals during their timelock
+#[cw_serde]
+pub struct Guardian {
+    pub chain: ChainName,
+    /// Address on `chain`
+    pub address: String,
+}
+
+/// Guardian set of the governance, spread over the members
+#[cw_serde]
+#[derive(Default)]
+pub struct Guardians {
+    pub members: Vec<Guardian>,
+}
+
+impl Guardians {
+    /// The guardian of `chain` with `address`, if any
+    pub fn find(&self, chain: &ChainName, address: &str) -> Option<&Guardian> {
+        self.members
+            .iter()
+            .find(|g| &g.chain == chain && g.address == address)
+    }
+}
+
+#[cw_serde]
+pub enum ActionResult {
+    /// Sent, waiting on the ack of the receiving chain
+    Pending,
+    Executed,
+    Failed {
+        error: String,
+    },
+}
+
+/// What happens to the deposit of a proposal that is rejected without reaching quorum
+#[cw_serde]
+pub enum DepositForfeit {
+    /// Return it to the proposer anyway
+    Refund,
+    /// Burn it
+    Burn,
+    /// Send it to the community pool of the chain, only for native deposits
+    CommunityPool,
+}
+
+#[cw_serde]
+pub struct DepositConfigMsg {
+    /// Asset that has to be deposited by the proposer
+    pub asset: AssetUnchecked,
+    /// Share of the proposal's members that have to vote for the deposit to be refunded when the
+    /// proposal is rejected. Only passed proposals are refunded if None.
+    pub quorum: Option<Decimal>,
+    pub forfeit: DepositForfeit,
+}
+
+impl DepositConfigMsg {
+    pub fn check(self, api: &dyn Api) -> Result<DepositConfig, InterchainGovError> {
+        let DepositConfigMsg {
+            asset,
+            quorum,
+            forfeit,
+        } = self;
+
+        let asset = asset.check(api, None)?;
+        if forfeit == DepositForfeit::CommunityPool && !matches!(asset.info, AssetInfo::Native(_)) {
+            return Err(InterchainGovError::InvalidDeposit(
+                "only native deposits can fund the community pool".to_string(),
+            ));
+        }
+        if quorum.is_some_and(|quorum| quorum > Decimal::one()) {
+            return Err(InterchainGovError::InvalidDeposit(
+                "quorum can't exceed 100%".to_string(),
+            ));
+        }
+        Ok(DepositConfig {
+            asset,
+            quorum,
+            forfeit,
+        })
+    }
+}
+
+/// Governance parameters shared by all members
+#[cw_serde]
+pub struct GovConfig {
+    /// Minimum voting period of proposals that don't set one
+    pub voting_period: Option<Duration>,
+    /// Share of the votes cast that have to be yes
+    pub threshold: PercentageThreshold,
+    /// Share of the members that have to vote, else the threshold applies to all members
+    pub quorum: Option<PercentageThreshold>,
+    /// Deposit required to create a proposal
+    pub deposit: Option<DepositConfig>,
+    /// Maximum number of members, unlimited if None
+    pub max_members: Option<u32>,
+    /// Time between a proposal passing and its action being executable, none if None
+    pub timelock: Option<Duration>,
+    /// Can veto proposals during their timelock
+    #[serde(default)]
+    pub guardians: Guardians,
+    /// Share of all members that has to vote yes on emergency proposals, disabled if None
+    #[serde(default)]
+    pub emergency_threshold: Option<PercentageThreshold>,
+    /// Names of the actions proposals can take, see [`ProposalAction::name`]. All if None.
+    /// `update_config` is always allowed so the config can't lock itself.
+    pub allowed_actions: Option<Vec<String>>,
+}
+
+impl Default for GovConfig {
+    fn default() -> Self {
+        GovConfig {
+            voting_period: None,
+            threshold: PercentageThreshold::Majority {},
+            quorum: None,
+            deposit: None,
+            max_members: None,
+            timelock: None,
+            guardians: Guardians::default(),
+            emergency_threshold: None,
+            allowed_actions: None,
+        }
+    }
+}
+
+impl GovConfig {
+    /// Check that `prop` can't pass before the voting period elapsed since it was created.
+    /// Proposals without a creation block are checked from `block`.
+    pub fn assert_voting_period(
+        &self,
+        prop: &Proposal,
+        block: &BlockInfo,
+    ) -> Result<(), InterchainGovError> {
+        // Emergency proposals pass as soon as they reach the emergency threshold
+        if prop.emergency && self.emergency_threshold.is_some() {
+            return Ok(());
+        }
+        let Some(period) = self.voting_period else {
+            return Ok(());
+        };
+        let required = period.after(prop.created.as_ref().unwrap_or(block));
+        match prop.min_voting_period {
+            Some(min_voting_period) if min_voting_period >= required => Ok(()),
+            _ => Err(InterchainGovError::VotingPeriodTooShort {
+                min_voting_period: required,
+            }),
+        }
+    }
+
+    /// Threshold of the proposals created with this config
+    pub fn threshold(&self) -> Threshold {
+        match self.quorum {
+            Some(quorum) => Threshold::ThresholdQuorum {
+                threshold: self.threshold,
+                quorum,
+            },
+            None => Threshold::AbsolutePercentage {
+                percentage: self.threshold,
+            },
+        }
+    }
+
+    pub fn validate(&self, api: &dyn Api) -> Result<(), InterchainGovError> {
+        self.threshold()
+            .validate()
+            .map_err(|e| InterchainGovError::InvalidConfig(e.to_string()))?;
+        if let Some(percentage) = self.emergency_threshold {
+            Threshold::AbsolutePercentage { percentage }
+                .validate()
+                .map_err(|e| InterchainGovError::InvalidConfig(e.to_string()))?;
+        }
+        if let Some(deposit) = self.deposit.clone() {
+            DepositConfigMsg {
+                asset: deposit.asset.into(),
+                quorum: deposit.quorum,
+                forfeit: deposit.forfeit,
+            }
+            .check(api)?;
+        }
+        Ok(())
+    }
+
+    /// Check that proposals can take the action
+    pub fn assert_action_allowed(&self, action: &ProposalAction) -> Result<(), InterchainGovError> {
+        match &self.allowed_actions {
+            Some(allowed)
+                if !matches!(action, ProposalAction::UpdateConfig { .. })
+                    && !allowed.iter().any(|name| name == action.name()) =>
+            {
+                Err(InterchainGovError::ActionNotAllowed {
+                    action: action.name().to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checked version of [`DepositConfigMsg`]
+#[cw_serde]
+pub struct DepositConfig {
+    pub asset: Asset,
+    pub quorum: Option<Decimal>,
+    pub forfeit: DepositForfeit,
+}
+
+/// Funds of a member in the treasury ledger.
+/// Both totals only grow, so deposits and spends can be recorded in any order.
+#[cw_serde]
+#[derive(Default)]
+pub struct TreasuryBalance {
+    pub deposited: Uint128,
+    pub spent: Uint128,
+}
+
+impl TreasuryBalance {
+    pub fn available(&self) -> Uint128 {
+        self.deposited.saturating_sub(self.spent)
+    }
+}
+
+/// Passed proposal waiting on its timelock
+#[cw_serde]
+pub struct QueuedProposal {
+    pub outcome: ProposalOutcome,
+    pub executable_at: Expiration,
+}
+
+/// Deposit held for a proposal until its outcome
+#[cw_serde]
+pub struct Deposit {
+    /// Account the deposit was taken from
+    pub depositor: Addr,
+    /// Config in place when the deposit was taken, it decides how the deposit is settled
+    pub config: DepositConfig,
+}
+
+/// Vote of a member that is counted for another member that didn't vote itself
+#[cw_serde]
+pub struct Delegation {
+    pub delegate: ChainName,
+    /// Names of the actions the delegation covers, see [`ProposalAction::name`]. All if None.
+    pub actions: Option<Vec<String>>,
+}
+
+impl Delegation {
+    /// Whether the delegate votes for the proposal, it has to cover every action it can execute
+    pub fn covers(&self, prop: &Proposal) -> bool {
+        match &self.actions {
+            Some(actions) => prop
+                .actions()
+                .iter()
+                .all(|action| actions.iter().any(|name| name == action.name())),
+            None => true,
+        }
+    }
+}
+
+/// Vote of a member as counted in the tally
+#[cw_serde]
+pub struct CountedVote {
+    pub chain: ChainName,
+    pub vote: Vote,
+    /// Delegate whose vote was counted, None if the member voted itself
+    pub delegate: Option<ChainName>,
 }
 
 /// Tally result from the other chain
@@ -0,0 +1,31 @@
+use cosmwasm_std::{to_json_vec, Binary, StdResult, Storage};
+
+use crate::contract::AdapterResult;
+use crate::state::{StateCommit, STATE_HASH};
+use crate::InterchainGovError;
+
+/// Current state hash, empty before the first commit
+pub(crate) fn load_state_hash(storage: &dyn Storage) -> StdResult<Binary> {
+    Ok(STATE_HASH.may_load(storage)?.unwrap_or_default())
+}
+
+/// Commit a change on top of the current state hash: `sha256(parent || change)`
+pub(crate) fn commit_state(storage: &mut dyn Storage, change: &StateCommit) -> StdResult<Binary> {
+    let mut preimage = load_state_hash(storage)?.to_vec();
+    preimage.extend(to_json_vec(change)?);
+    let hash = Binary::from(<sha2::Sha256 as sha2::Digest>::digest(preimage).to_vec());
+    STATE_HASH.save(storage, &hash)?;
+    Ok(hash)
+}
+
+/// Refuse messages that were built on a different state than ours
+pub(crate) fn assert_parent_hash(storage: &dyn Storage, parent_hash: &Binary) -> AdapterResult<()> {
+    let expected = load_state_hash(storage)?;
+    if &expected != parent_hash {
+        return Err(InterchainGovError::StateHashMismatch {
+            expected: expected.to_base64(),
+            actual: parent_hash.to_base64(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,299 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use abstract_adapter::objects::ChannelEntry;
+use abstract_adapter::sdk::IbcInterface;
+use abstract_adapter::std::{proxy, ICS20};
+use abstract_adapter::traits::AbstractNameService;
+use cosmwasm_std::{
+    wasm_execute, Addr, Api, BankMsg, Coin, CosmosMsg, DepsMut, Env, IbcMsg, Order, StdResult,
+    Storage, SubMsg,
+};
+
+use crate::contract::{AdapterResult, InterchainGov};
+use crate::handlers::execute::this_module;
+use crate::msg::{InterchainGovIbcMsg, InterchainGovIbcPacket};
+use crate::replies::TRANSFER_REPLY_ID;
+use crate::state::{
+    ActionResult, ProposalId, FINALIZED_PROPOSALS, GOVERNING_ACCOUNT, MEMBERS_STATE_SYNC,
+    PENDING_TRANSFERS, TRANSFER_PACKETS, TREASURY,
+};
+use crate::state_hash::load_state_hash;
+use crate::InterchainGovError;
+
+/// Seconds before an ICS-20 transfer from the treasury times out
+pub const TRANSFER_TIMEOUT: u64 = 60 * 60;
+/// Chains that report the ack or timeout of a transfer to the contract that sent it, through the
+/// Neutron sudo callbacks. Elsewhere a transfer would stay pending forever.
+pub const TRANSFER_CALLBACK_CHAINS: &[&str] = &["neutron", "pion"];
+
+/// Record funds pledged to the treasury by `chain`
+pub(crate) fn record_deposit(
+    storage: &mut dyn Storage,
+    chain: &ChainName,
+    funds: &[Coin],
+) -> StdResult<()> {
+    for coin in funds {
+        TREASURY.update(storage, (chain, &coin.denom), |balance| {
+            let mut balance = balance.unwrap_or_default();
+            balance.deposited += coin.amount;
+            StdResult::Ok(balance)
+        })?;
+    }
+    Ok(())
+}
+
+/// Funds of `chain` that are still available in the treasury
+pub(crate) fn treasury_balance(
+    storage: &dyn Storage,
+    chain: &ChainName,
+    denom: &str,
+) -> StdResult<Coin> {
+    let balance = TREASURY
+        .may_load(storage, (chain, denom))?
+        .unwrap_or_default();
+    Ok(Coin::new(balance.available().u128(), denom))
+}
+
+/// Every member's available treasury funds
+pub(crate) fn treasury_balances(storage: &dyn Storage) -> StdResult<Vec<(ChainName, Vec<Coin>)>> {
+    let mut balances: Vec<(ChainName, Vec<Coin>)> = vec![];
+    for entry in TREASURY.range(storage, None, None, Order::Ascending) {
+        let ((chain, denom), balance) = entry?;
+        let coin = Coin::new(balance.available().u128(), denom);
+        match balances.last_mut() {
+            Some((last, coins)) if last == &chain => coins.push(coin),
+            _ => balances.push((chain, vec![coin])),
+        }
+    }
+    Ok(balances)
+}
+
+/// Check that the treasury of `chain` holds enough funds for the spend
+pub(crate) fn assert_treasury_funds(
+    storage: &dyn Storage,
+    chain: &ChainName,
+    amount: &[Coin],
+) -> AdapterResult<()> {
+    for coin in amount {
+        if treasury_balance(storage, chain, &coin.denom)?.amount < coin.amount {
+            return Err(InterchainGovError::InsufficientTreasury {
+                chain: chain.clone(),
+                amount: coin.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that `chain` tells the adapter whether its transfers arrived
+pub(crate) fn assert_transfer_callbacks(chain: &ChainName) -> AdapterResult<()> {
+    if !TRANSFER_CALLBACK_CHAINS.contains(&chain.as_str()) {
+        return Err(InterchainGovError::TransferCallbacksUnsupported {
+            chain: chain.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Record a spend from the treasury of `chain`.
+///
+/// Spends are recorded on every member, only the chain holding the funds sends them from its
+/// governing account.
+pub(crate) fn spend_from_treasury(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    chain: &ChainName,
+    recipient: &str,
+    amount: &[Coin],
+) -> AdapterResult<Vec<SubMsg>> {
+    assert_treasury_funds(storage, chain, amount)?;
+    record_spend(storage, chain, amount)?;
+    if chain != &ChainName::new(env) {
+        return Ok(vec![]);
+    }
+
+    let send = BankMsg::Send {
+        to_address: api.addr_validate(recipient)?.to_string(),
+        amount: amount.to_vec(),
+    };
+    Ok(vec![SubMsg::new(governing_account_msg(
+        storage,
+        vec![send.into()],
+    )?)])
+}
+
+/// Record an ICS-20 transfer from the treasury of `from_chain` to `recipient` on `to_chain`.
+///
+/// Like [`spend_from_treasury`], only `from_chain` sends the funds. The governing account hands
+/// them to the adapter, which sends the transfers so that their ack or timeout comes back to it.
+/// Whether a transfer could be sent is handled in the [`TRANSFER_REPLY_ID`] reply, whether it
+/// arrived in [`settle_transfer`].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn ibc_transfer(
+    deps: DepsMut,
+    env: &Env,
+    app: &InterchainGov,
+    prop_id: &ProposalId,
+    from_chain: &ChainName,
+    to_chain: &ChainName,
+    recipient: &str,
+    amount: &[Coin],
+) -> AdapterResult<Vec<SubMsg>> {
+    assert_treasury_funds(deps.storage, from_chain, amount)?;
+    record_spend(deps.storage, from_chain, amount)?;
+    if from_chain != &ChainName::new(env) {
+        return Ok(vec![]);
+    }
+
+    let channel_id = app.name_service(deps.as_ref()).query(&ChannelEntry {
+        connected_chain: to_chain.clone(),
+        protocol: ICS20.to_string(),
+    })?;
+    let withdraw = BankMsg::Send {
+        to_address: env.contract.address.to_string(),
+        amount: amount.to_vec(),
+    };
+    let mut msgs = vec![SubMsg::new(governing_account_msg(
+        deps.storage,
+        vec![withdraw.into()],
+    )?)];
+    for coin in amount {
+        let transfer = IbcMsg::Transfer {
+            channel_id: channel_id.clone(),
+            to_address: recipient.to_string(),
+            amount: coin.clone(),
+            timeout: env.block.time.plus_seconds(TRANSFER_TIMEOUT).into(),
+        };
+        PENDING_TRANSFERS.push_back(deps.storage, &(prop_id.clone(), coin.clone()))?;
+        msgs.push(SubMsg::reply_always(transfer, TRANSFER_REPLY_ID));
+    }
+    Ok(msgs)
+}
+
+/// Settle the ICS-20 transfer of `coin` for `prop_id`.
+///
+/// A failed transfer is taken out of the ledger again and its funds, held by the adapter, are
+/// returned to the governing account. The result is reported to the other members once it is
+/// known, which also undo the spend of a failed transfer.
+pub(crate) fn settle_transfer(
+    deps: DepsMut,
+    env: &Env,
+    app: &InterchainGov,
+    prop_id: &ProposalId,
+    coin: Coin,
+    failure: Option<String>,
+) -> AdapterResult<Vec<CosmosMsg>> {
+    let (prop, mut outcome) = FINALIZED_PROPOSALS.load(deps.storage, prop_id.clone())?;
+    let mut msgs = vec![];
+    let mut refunded = vec![];
+    let result = match failure {
+        Some(error) => {
+            record_refund(
+                deps.storage,
+                &ChainName::new(env),
+                std::slice::from_ref(&coin),
+            )?;
+            let account = governing_account(deps.storage)?;
+            msgs.push(
+                BankMsg::Send {
+                    to_address: account.to_string(),
+                    amount: vec![coin.clone()],
+                }
+                .into(),
+            );
+            refunded.push(coin);
+            ActionResult::Failed { error }
+        }
+        // An earlier transfer of the proposal failed
+        None if matches!(outcome.action_result, Some(ActionResult::Failed { .. })) => {
+            return Ok(msgs);
+        }
+        None if transfers_pending(deps.storage, prop_id)? => ActionResult::Pending,
+        None => ActionResult::Executed,
+    };
+    outcome.action_result = Some(result.clone());
+    FINALIZED_PROPOSALS.save(deps.storage, prop_id.clone(), &(prop, outcome))?;
+    if result == ActionResult::Pending {
+        return Ok(msgs);
+    }
+
+    let ibc_client = app.ibc_client(deps.as_ref());
+    let packet = InterchainGovIbcPacket::new(
+        load_state_hash(deps.storage)?,
+        InterchainGovIbcMsg::ActionResult {
+            prop_hash: prop_id.clone(),
+            result,
+            refunded,
+        },
+    );
+    for member in MEMBERS_STATE_SYNC
+        .external_members(deps.storage, env)?
+        .members
+    {
+        msgs.push(ibc_client.module_ibc_action(
+            member.to_string(),
+            this_module(app)?,
+            &packet,
+            None,
+        )?);
+    }
+    Ok(msgs)
+}
+
+/// Whether transfers of `prop_id` still wait on their reply, ack or timeout
+fn transfers_pending(storage: &dyn Storage, prop_id: &ProposalId) -> StdResult<bool> {
+    for transfer in PENDING_TRANSFERS.iter(storage)? {
+        if &transfer?.0 == prop_id {
+            return Ok(true);
+        }
+    }
+    for packet in TRANSFER_PACKETS.range(storage, None, None, Order::Ascending) {
+        if &packet?.1 .0 == prop_id {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn record_spend(storage: &mut dyn Storage, chain: &ChainName, amount: &[Coin]) -> StdResult<()> {
+    for coin in amount {
+        TREASURY.update(storage, (chain, &coin.denom), |balance| {
+            let mut balance = balance.unwrap_or_default();
+            balance.spent += coin.amount;
+            StdResult::Ok(balance)
+        })?;
+    }
+    Ok(())
+}
+
+/// Undo a recorded spend from the treasury of `chain`
+pub(crate) fn record_refund(
+    storage: &mut dyn Storage,
+    chain: &ChainName,
+    amount: &[Coin],
+) -> StdResult<()> {
+    for coin in amount {
+        TREASURY.update(storage, (chain, &coin.denom), |balance| {
+            let mut balance = balance.unwrap_or_default();
+            balance.spent = balance.spent.checked_sub(coin.amount)?;
+            StdResult::Ok(balance)
+        })?;
+    }
+    Ok(())
+}
+
+/// Proxy of the account governed by this adapter
+pub(crate) fn governing_account(storage: &dyn Storage) -> AdapterResult<Addr> {
+    GOVERNING_ACCOUNT
+        .may_load(storage)?
+        .ok_or(InterchainGovError::NoGoverningAccount)
+}
+
+/// Execute `msgs` from the governing account
+pub(crate) fn governing_account_msg(
+    storage: &dyn Storage,
+    msgs: Vec<CosmosMsg>,
+) -> AdapterResult<CosmosMsg> {
+    let account = governing_account(storage)?;
+    Ok(wasm_execute(account, &proxy::ExecuteMsg::ModuleAction { msgs }, vec![])?.into())
+}
@@ -0,0 +1,27 @@
+use semver::Version;
+
+use crate::contract::AdapterResult;
+use crate::{InterchainGovError, ADAPTER_VERSION};
+
+/// Check that a packet sent by an adapter at `version` can be handled
+pub(crate) fn assert_compatible_version(version: &str) -> AdapterResult<()> {
+    if !compatible(&parse_version(ADAPTER_VERSION)?, &parse_version(version)?) {
+        return Err(InterchainGovError::IncompatibleVersion {
+            version: version.to_string(),
+            expected: ADAPTER_VERSION.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_version(version: &str) -> AdapterResult<Version> {
+    Version::parse(version).map_err(|e| InterchainGovError::InvalidVersion {
+        version: version.to_string(),
+        error: e.to_string(),
+    })
+}
+
+/// Versions with the same major, or the same minor before 1.0, speak the same IBC messages
+fn compatible(ours: &Version, theirs: &Version) -> bool {
+    ours.major == theirs.major && (ours.major > 0 || ours.minor == theirs.minor)
+}
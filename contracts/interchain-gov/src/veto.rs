@@ -0,0 +1,96 @@
+use cosmwasm_std::{CosmosMsg, Env, Storage};
+
+use crate::contract::AdapterResult;
+use crate::deposit::settle_deposit;
+use crate::handlers::execute::{outcome_packets, OutgoingPacket};
+use crate::state::{
+    load_gov_config, Authority, GovConfig, Guardians, ProposalId, ProposalOutcome, StateCommit,
+    Veto, FINALIZED_PROPOSALS, GOV_CONFIG, MEMBERS_STATE_SYNC, PROPOSAL_STATE_SYNC,
+    QUEUED_PROPOSALS,
+};
+use crate::state_hash::{commit_state, load_state_hash};
+use crate::InterchainGovError;
+
+/// Veto a queued proposal of this chain.
+///
+/// The proposal fails without executing its action, its deposit is forfeited and the vetoed
+/// outcome is reported to the other members like any other outcome.
+/// Returns the packets for the other members and the deposit messages.
+pub(crate) fn veto_queued(
+    storage: &mut dyn Storage,
+    env: &Env,
+    prop_id: ProposalId,
+    veto: Veto,
+) -> AdapterResult<(Vec<OutgoingPacket>, Vec<CosmosMsg>)> {
+    let Some(queued) = QUEUED_PROPOSALS.may_load(storage, prop_id.clone())? else {
+        return Err(InterchainGovError::ProposalNotQueued(prop_id));
+    };
+    if queued.executable_at.is_expired(&env.block) {
+        return Err(InterchainGovError::TimelockExpired(prop_id));
+    }
+    QUEUED_PROPOSALS.remove(storage, prop_id.clone());
+
+    let prop = PROPOSAL_STATE_SYNC.load(storage, prop_id.clone())?.0;
+    let outcome = ProposalOutcome {
+        passed: false,
+        vetoed: Some(veto),
+        ..queued.outcome
+    };
+    FINALIZED_PROPOSALS.save(storage, prop_id.clone(), &(prop.clone(), outcome.clone()))?;
+    let parent_hash = load_state_hash(storage)?;
+    commit_state(
+        storage,
+        &StateCommit::Outcome {
+            prop_id: prop_id.clone(),
+            outcome: outcome.clone(),
+        },
+    )?;
+    let deposit_msgs = settle_deposit(storage, &prop_id, &prop, &outcome)?;
+
+    let external_members = MEMBERS_STATE_SYNC.external_members(storage, env)?;
+    let packets = outcome_packets(
+        storage,
+        parent_hash,
+        &prop_id,
+        outcome,
+        &external_members.members,
+    )?;
+    Ok((packets, deposit_msgs))
+}
+
+/// Veto `target` as the action of the emergency proposal `prop_id`.
+///
+/// Only the chain that queued `target` can veto it, the other members skip the action.
+pub(crate) fn veto_by_proposal(
+    storage: &mut dyn Storage,
+    env: &Env,
+    prop_id: &ProposalId,
+    target: ProposalId,
+    reason: String,
+) -> AdapterResult<(Vec<OutgoingPacket>, Vec<CosmosMsg>)> {
+    match QUEUED_PROPOSALS.may_load(storage, target.clone())? {
+        Some(queued) if !queued.executable_at.is_expired(&env.block) => {}
+        _ => return Ok((vec![], vec![])),
+    }
+    let veto = Veto {
+        by: Authority::Proposal(prop_id.clone()),
+        reason,
+    };
+    veto_queued(storage, env, target, veto)
+}
+
+/// Replace the guardian set, applied by every member when the proposal result arrives
+pub(crate) fn update_guardians(
+    storage: &mut dyn Storage,
+    guardians: Guardians,
+) -> AdapterResult<()> {
+    let config = load_gov_config(storage)?;
+    GOV_CONFIG.save(
+        storage,
+        &GovConfig {
+            guardians,
+            ..config
+        },
+    )?;
+    Ok(())
+}
@@ -1,13 +1,18 @@
 use abstract_adapter::objects::chain_name::ChainName;
-use ibc_sync_state::DataState;
+use ibc_sync_state::{DataState, SyncAction};
 use interchain_gov::{
     contract::interface::InterchainGovInterface,
-    msg::{InterchainGovInstantiateMsg, InterchainGovQueryMsgFns},
-    state::Members,
+    msg::{InterchainGovExecuteMsgFns, InterchainGovInstantiateMsg, InterchainGovQueryMsgFns},
+    state::{DepositConfigMsg, Governance, Members, Vote},
     InterchainGovExecuteMsg, MY_ADAPTER_ID, MY_NAMESPACE,
 };
 
+use abstract_adapter::objects::module::ModuleInfo;
+use abstract_adapter::objects::UncheckedChannelEntry;
+use abstract_adapter::std::ans_host::ExecuteMsgFns as AnsExecuteMsgFns;
+use abstract_adapter::std::ibc::ModuleIbcMsg;
 use abstract_adapter::std::manager::ExecuteMsgFns;
+use abstract_adapter::std::ICS20;
 use abstract_adapter::std::{adapter, objects::namespace::Namespace};
 use abstract_client::{AbstractClient, Account, Application, Environment, Publisher};
 use abstract_cw_orch_polytone::Polytone;
@@ -20,10 +25,15 @@ use cw_orch::{anyhow, prelude::*};
 use speculoos::prelude::*;
 
 use abstract_interchain_tests::setup::ibc_connect_polytone_and_abstract;
+use abstract_interface::Abstract;
 
+use cosmwasm_std::to_json_binary;
 use cw_orch::tokio::runtime::Runtime;
 use cw_utils::Expiration;
-use interchain_gov::state::{ProposalAction, ProposalId, ProposalMsg};
+use ibc_relayer_types::core::ics24_host::identifier::PortId;
+use interchain_gov::msg::{InterchainGovIbcMsg, InterchainGovIbcPacket};
+use interchain_gov::state::{ProposalAction, ProposalId, ProposalMsg, ProposalOutcome};
+use interchain_gov::ADAPTER_VERSION;
 // use cw_orch_interchain::MockBech32InterchainEnv;
 
 const A_CHAIN_ID: &str = "neutron-1";
@@ -43,6 +53,14 @@ struct TestEnv<Env: CwEnv> {
 impl<Env: CwEnv> TestEnv<Env> {
     /// Set up the test environment with an Account that has the Adapter installed
     fn setup(env: Env) -> anyhow::Result<TestEnv<Env>> {
+        Self::setup_with_deposit(env, None)
+    }
+
+    /// Same as [`TestEnv::setup`] with a deposit required to create proposals
+    fn setup_with_deposit(
+        env: Env,
+        deposit: Option<DepositConfigMsg>,
+    ) -> anyhow::Result<TestEnv<Env>> {
         // Create a sender and mock env
         let sender = env.sender();
         let namespace = Namespace::new(MY_NAMESPACE)?;
@@ -57,12 +75,14 @@ impl<Env: CwEnv> TestEnv<Env> {
             .build()?;
         publisher.publish_adapter::<InterchainGovInstantiateMsg, InterchainGovInterface<_>>(
             InterchainGovInstantiateMsg {
+                account: publisher.account().proxy()?.to_string(),
                 accept_proposal_from_gov: Members {
                     members: vec![
                         ChainName::from_chain_id(A_CHAIN_ID),
                         ChainName::from_chain_id(B_CHAIN_ID),
                     ],
                 },
+                deposit,
             },
         )?;
         // Enable IBC on the account
@@ -198,97 +218,1231 @@ impl<Env: CwEnv> TestEnv<Env> {
 
         let id: String = test_prop.hash();
 
-        let props = self.gov.list_proposals()?.proposals;
-        let prop_id = props.into_iter().find(|p| p.prop_id == id).unwrap().prop_id;
-        Ok((res, prop_id))
+        let props = self.gov.list_proposals()?.proposals;
+        let prop_id = props.into_iter().find(|p| p.prop_id == id).unwrap().prop_id;
+        Ok((res, prop_id))
+    }
+
+    fn finalize_proposal(&self, prop_id: ProposalId) -> anyhow::Result<Env::Response> {
+        let res = self.execute_gov(InterchainGovExecuteMsg::Finalize { prop_id })?;
+
+        Ok(res)
+    }
+
+    fn assert_prop_state(
+        &self,
+        prop_id: ProposalId,
+        expected_state: Option<DataState>,
+    ) -> anyhow::Result<()> {
+        let state = self.gov.proposal_state(prop_id)?.map(|f| f.state);
+        assert_that!(state).is_equal_to(expected_state);
+        Ok(())
+    }
+
+    fn test_register_gov_modules(
+        &self,
+        govs: Vec<(ChainName, InterchainGovInterface<MockBech32>)>,
+    ) -> anyhow::Result<()> {
+        let modules = govs
+            .into_iter()
+            .map(|(chain_name, gov)| (chain_name, gov.address().unwrap().to_string()))
+            .collect();
+        self.execute_gov(
+            InterchainGovExecuteMsg::TemporaryRegisterRemoteGovModuleAddrs { modules },
+        )?;
+        Ok(())
+    }
+}
+
+fn test_proposal(title: impl Into<String>, action: ProposalAction, exp_height: u64) -> ProposalMsg {
+    ProposalMsg {
+        title: title.into(),
+        description: "This is a test proposal".to_string(),
+        min_voting_period: None,
+        expiration: Expiration::AtHeight(exp_height),
+        action,
+        emergency: false,
+        choices: None,
+    }
+}
+
+/// Set up A and B as the members of a governance
+fn setup_governance(
+    interchain: &MockBech32InterchainEnv,
+) -> anyhow::Result<(TestEnv<MockBech32>, TestEnv<MockBech32>)> {
+    let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+    let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+
+    a_env.enable_ibc()?;
+    b_env.enable_ibc()?;
+    ibc_connect_polytone_and_abstract(interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+    a_env.test_register_gov_modules(vec![(b_env.chain_name(), b_env.gov.clone())])?;
+
+    let members: Members = vec![a_env.chain_name(), b_env.chain_name()].into();
+    a_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+        members: members.clone(),
+    })?;
+    b_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers { members })?;
+    Ok((a_env, b_env))
+}
+
+/// Deliver `packet` to the adapter of `to` as if the adapter on `from` sent it
+fn deliver_packet(
+    to: &TestEnv<MockBech32>,
+    from: ChainName,
+    packet: &InterchainGovIbcPacket,
+) -> anyhow::Result<<MockBech32 as TxHandler>::Response> {
+    let chain = to.environment();
+    let host = Abstract::load_from(chain.clone())?.ibc.host.address()?;
+    let msg = adapter::ExecuteMsg::<InterchainGovExecuteMsg, Empty>::ModuleIbc(ModuleIbcMsg {
+        client_chain: from,
+        source_module: ModuleInfo::from_id(MY_ADAPTER_ID, ADAPTER_VERSION.into())?,
+        msg: to_json_binary(packet)?,
+    });
+    Ok(chain
+        .call_as(&host)
+        .execute(&msg, &[], &to.gov.address()?)?)
+}
+
+/// Open an ICS-20 channel from A to B and register it in A's name service
+fn connect_ics20(
+    interchain: &MockBech32InterchainEnv,
+    a_env: &TestEnv<MockBech32>,
+) -> anyhow::Result<()> {
+    let channel = interchain.create_channel(
+        A_CHAIN_ID,
+        B_CHAIN_ID,
+        &PortId::transfer(),
+        &PortId::transfer(),
+        "ics20-1",
+        None,
+    )?;
+    let channel_id = channel
+        .interchain_channel
+        .get_chain(A_CHAIN_ID)?
+        .channel
+        .unwrap();
+    a_env.abs.name_service().update_channels(
+        vec![(
+            UncheckedChannelEntry {
+                connected_chain: ChainName::from_chain_id(B_CHAIN_ID).to_string(),
+                protocol: ICS20.to_string(),
+            },
+            channel_id.to_string(),
+        )],
+        vec![],
+    )?;
+    Ok(())
+}
+
+/// Propose `action` on A, vote yes on A and `b_vote` on B, then collect the votes once it expired
+fn vote_proposal(
+    interchain: &MockBech32InterchainEnv,
+    a_env: &TestEnv<MockBech32>,
+    b_env: &TestEnv<MockBech32>,
+    title: &str,
+    action: ProposalAction,
+    b_vote: Vote,
+) -> anyhow::Result<ProposalId> {
+    let (res, prop_id) = a_env.propose_proposal(title, action)?;
+    interchain.wait_ibc(A_CHAIN_ID, res)?;
+    let res = a_env.finalize_proposal(prop_id.clone())?;
+    interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+    a_env
+        .gov
+        .vote_proposal(Governance::Manual {}, prop_id.clone(), Vote::Yes)?;
+    b_env
+        .gov
+        .vote_proposal(Governance::Manual {}, prop_id.clone(), b_vote)?;
+    a_env.wait_blocks(TEST_PROP_LEN + 1)?;
+    b_env.wait_blocks(TEST_PROP_LEN + 1)?;
+
+    let res = a_env.gov.request_vote_results(prop_id.clone())?;
+    interchain.wait_ibc(A_CHAIN_ID, res)?;
+    Ok(prop_id)
+}
+
+mod propose {
+
+    use super::*;
+    use dao_voting::threshold::{PercentageThreshold, Threshold};
+    use interchain_gov::state::Proposal;
+
+    #[test]
+    fn only_members_propose() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let expiration = b_env.environment().block_info()?.height + TEST_PROP_LEN;
+        let proposal = |proposer_chain: ChainName| Proposal {
+            title: "forged".to_string(),
+            description: "Not proposed by its sender".to_string(),
+            proposer: "proposer".to_string(),
+            proposer_chain,
+            action: ProposalAction::Signal,
+            min_voting_period: None,
+            expiration: Expiration::AtHeight(expiration),
+            created: None,
+            threshold: Threshold::AbsolutePercentage {
+                percentage: PercentageThreshold::Majority {},
+            },
+            epoch: 0,
+            members: vec![a_env.chain_name(), b_env.chain_name()].into(),
+            emergency: false,
+            choices: None,
+        };
+        let propose = |proposer_chain: ChainName| -> anyhow::Result<InterchainGovIbcPacket> {
+            Ok(InterchainGovIbcPacket::new(
+                b_env.gov.state_hash()?.hash,
+                InterchainGovIbcMsg::ProposeProposal {
+                    prop_hash: "forged".to_string(),
+                    prop: proposal(proposer_chain),
+                    chain: b_env.chain_name(),
+                },
+            ))
+        };
+        let finalize = || -> anyhow::Result<InterchainGovIbcPacket> {
+            Ok(InterchainGovIbcPacket::new(
+                b_env.gov.state_hash()?.hash,
+                InterchainGovIbcMsg::FinalizeProposal {
+                    prop_hash: "forged".to_string(),
+                },
+            ))
+        };
+        let c_chain = ChainName::from_chain_id(C_CHAIN_ID);
+
+        // A chain outside of the governance can't propose, a member only its own proposals
+        let res = deliver_packet(&b_env, c_chain.clone(), &propose(c_chain.clone())?);
+        assert_that!(res).is_err();
+        let res = deliver_packet(&b_env, a_env.chain_name(), &propose(b_env.chain_name())?);
+        assert_that!(res).is_err();
+        deliver_packet(&b_env, a_env.chain_name(), &propose(a_env.chain_name())?)?;
+
+        // Only the proposing chain finalizes it
+        let res = deliver_packet(&b_env, c_chain, &finalize()?);
+        assert_that!(res).is_err();
+        b_env.assert_prop_state("forged".to_string(), Some(DataState::Proposed))?;
+        deliver_packet(&b_env, a_env.chain_name(), &finalize()?)?;
+        b_env.assert_prop_state("forged".to_string(), None)?;
+        Ok(())
+    }
+
+    #[test]
+    fn happy_propose() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+        let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+
+        a_env.enable_ibc()?;
+        b_env.enable_ibc()?;
+        ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+
+        let a_gov = a_env.gov.clone();
+        let b_gov = b_env.gov.clone();
+
+        a_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![b_env.chain_name(), a_env.chain_name()].into(),
+        })?;
+
+        b_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![a_env.chain_name(), b_env.chain_name()].into(),
+        })?;
+
+        // Create proposal
+        let (res, prop_id) = a_env.propose_proposal("happy_propose", ProposalAction::Signal)?;
+
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let proposals = a_gov.list_proposal_states()?;
+        assert_that!(proposals.state.len()).is_equal_to(0);
+
+        let proposals = b_gov.list_proposal_states()?;
+        assert_that!(proposals.state.len()).is_equal_to(1);
+
+        // check the state.
+        // should be proposed on A and pending on B
+        a_env.assert_prop_state(prop_id.clone(), None)?;
+        b_env.assert_prop_state(prop_id, Some(DataState::Proposed))?;
+
+        Ok(())
+    }
+}
+
+mod deposit {
+    use super::*;
+    use cosmwasm_std::coins;
+    use cw_asset::AssetUnchecked;
+    use interchain_gov::state::DepositForfeit;
+
+    #[test]
+    fn deposit_taken_on_propose() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        let a_env = TestEnv::setup_with_deposit(
+            chain.clone(),
+            Some(DepositConfigMsg {
+                asset: AssetUnchecked::native("untrn", 100u128),
+                quorum: None,
+                forfeit: DepositForfeit::Burn,
+            }),
+        )?;
+        let proxy = a_env.gov.account().proxy()?;
+        chain.add_balance(&proxy, coins(150, "untrn"))?;
+
+        let (_, prop_id) =
+            a_env.propose_first_member_proposal("deposit", ProposalAction::Signal)?;
+
+        // The deposit is held by the adapter until the proposal has an outcome
+        let deposit = a_env.gov.deposit(prop_id)?.unwrap();
+        assert_that!(deposit.depositor).is_equal_to(proxy.clone());
+        assert_that!(chain.query_balance(&proxy, "untrn")?.u128()).is_equal_to(50);
+        assert_that!(chain.query_balance(&a_env.gov.address()?, "untrn")?.u128()).is_equal_to(100);
+
+        // Not enough funds left for another deposit
+        let res = a_env.propose_first_member_proposal("no deposit", ProposalAction::Signal);
+        assert_that!(res).is_err();
+
+        Ok(())
+    }
+}
+
+mod config {
+    use super::*;
+    use dao_voting::threshold::PercentageThreshold;
+
+    #[test]
+    fn default_config() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let res = a_env.gov.config()?;
+        assert_that!(res.config.threshold).is_equal_to(PercentageThreshold::Majority {});
+        assert_that!(res.config.deposit).is_none();
+        assert_that!(res.pending).is_none();
+
+        Ok(())
+    }
+}
+
+mod timelock {
+    use super::*;
+    use cw_utils::Duration;
+    use dao_voting::threshold::PercentageThreshold;
+    use interchain_gov::state::{GovConfig, Governance};
+
+    #[test]
+    fn execute_queued_requires_queue() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let (_, prop_id) = a_env.propose_first_member_proposal("queued", ProposalAction::Signal)?;
+        assert_that!(a_env.gov.queued_proposals()?.proposals).is_empty();
+
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::ExecuteQueued { prop_id });
+        assert_that!(res).is_err();
+
+        Ok(())
+    }
+
+    #[test]
+    fn executes_queued_once() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let config = GovConfig {
+            timelock: Some(Duration::Height(10)),
+            ..a_env.gov.config()?.config
+        };
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "timelock",
+            ProposalAction::UpdateConfig { config },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "queued",
+            ProposalAction::Signal,
+            Vote::Yes,
+        )?;
+        a_env.gov.execute_proposal(prop_id.clone())?;
+        assert_that!(a_env.gov.queued_proposals()?.proposals).has_length(1);
+        // Executing it again doesn't restart the timelock
+        assert_that!(a_env.gov.execute_proposal(prop_id.clone())).is_err();
+
+        a_env.wait_blocks(10)?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::ExecuteQueued {
+            prop_id: prop_id.clone(),
+        })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        assert_that!(a_env.gov.queued_proposals()?.proposals).is_empty();
+
+        // Once executed it can't be executed or queued again
+        assert_that!(a_env.gov.execute_proposal(prop_id.clone())).is_err();
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::ExecuteQueued { prop_id });
+        assert_that!(res).is_err();
+        Ok(())
+    }
+
+    #[test]
+    fn emergency_veto_sent_in_one_packet() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let config = GovConfig {
+            timelock: Some(Duration::Height(10)),
+            emergency_threshold: Some(PercentageThreshold::Majority {}),
+            ..a_env.gov.config()?.config
+        };
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "timelock",
+            ProposalAction::UpdateConfig { config },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let queued = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "queued",
+            ProposalAction::Signal,
+            Vote::Yes,
+        )?;
+        a_env.gov.execute_proposal(queued.clone())?;
+
+        let proposal = ProposalMsg {
+            emergency: true,
+            ..test_proposal(
+                "veto",
+                ProposalAction::VetoProposal {
+                    prop_id: queued.clone(),
+                    reason: "emergency".to_string(),
+                },
+                a_env.environment().block_info()?.height + TEST_PROP_LEN,
+            )
+        };
+        let veto_id = proposal.hash();
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        let res = a_env.finalize_proposal(veto_id.clone())?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        for env in [&a_env, &b_env] {
+            env.gov
+                .vote_proposal(Governance::Manual {}, veto_id.clone(), Vote::Yes)?;
+        }
+        let res = a_env.gov.request_vote_results(veto_id.clone())?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        // Both outcomes go to B in a single packet
+        let res = a_env.gov.execute_proposal(veto_id)?;
+        let packets = res
+            .events
+            .iter()
+            .filter(|event| event.ty == "send_packet")
+            .count();
+        assert_that!(packets).is_equal_to(1);
+        interchain.wait_ibc(A_CHAIN_ID, res)?.into_result()?;
+
+        assert_that!(a_env.gov.queued_proposals()?.proposals).is_empty();
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::ExecuteQueued { prop_id: queued });
+        assert_that!(res).is_err();
+        assert_eq!(a_env.gov.state_hash()?.hash, b_env.gov.state_hash()?.hash);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_guardians_veto() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let (_, prop_id) = a_env.propose_first_member_proposal("veto", ProposalAction::Signal)?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Veto {
+            prop_id,
+            reason: "not a guardian".to_string(),
+        });
+        assert_that!(res).is_err();
+
+        Ok(())
+    }
+}
+
+mod voting_period {
+    use super::*;
+    use cw_utils::Duration;
+    use dao_voting::threshold::PercentageThreshold;
+    use interchain_gov::state::GovConfig;
+
+    #[test]
+    fn min_voting_period_enforced() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let config = GovConfig {
+            voting_period: Some(Duration::Height(20)),
+            ..a_env.gov.config()?.config
+        };
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "voting_period",
+            ProposalAction::UpdateConfig { config },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        // Shorter than the voting period
+        let height = a_env.environment().block_info()?.height;
+        let proposal = ProposalMsg {
+            min_voting_period: Some(Expiration::AtHeight(height + 5)),
+            ..test_proposal("too_short", ProposalAction::Signal, height + 30)
+        };
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal });
+        assert_that!(res).is_err();
+        assert_that!(a_env.gov.list_proposal_states()?.state).is_empty();
+
+        // Without one the voting period applies
+        let (res, _) = a_env.propose_proposal("default", ProposalAction::Signal)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        assert_that!(b_env.gov.list_proposal_states()?.state).has_length(1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn emergency_skips_voting_period() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let config = GovConfig {
+            voting_period: Some(Duration::Height(20)),
+            emergency_threshold: Some(PercentageThreshold::Majority {}),
+            ..a_env.gov.config()?.config
+        };
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "voting_period",
+            ProposalAction::UpdateConfig { config },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let height = a_env.environment().block_info()?.height;
+        let proposal = ProposalMsg {
+            emergency: true,
+            ..test_proposal(
+                "emergency",
+                ProposalAction::Pause {
+                    reason: "emergency".to_string(),
+                },
+                height + TEST_PROP_LEN,
+            )
+        };
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        assert_that!(b_env.gov.list_proposal_states()?.state).has_length(1);
+
+        // A regular proposal with the same period is still rejected
+        let proposal = ProposalMsg {
+            min_voting_period: Some(Expiration::AtHeight(height + 5)),
+            ..test_proposal("too_short", ProposalAction::Signal, height + 30)
+        };
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal });
+        assert_that!(res).is_err();
+
+        Ok(())
+    }
+}
+
+mod emergency {
+    use super::*;
+    use interchain_gov::state::Guardians;
+
+    #[test]
+    fn emergency_disabled_by_default() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let proposal = ProposalMsg {
+            emergency: true,
+            ..test_proposal(
+                "emergency",
+                ProposalAction::UpdateGuardians {
+                    guardians: Guardians::default(),
+                },
+                a_env.environment().block_info()?.height + TEST_PROP_LEN,
+            )
+        };
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal });
+        assert_that!(res).is_err();
+        assert_that!(a_env.gov.list_proposals()?.proposals).is_empty();
+
+        Ok(())
+    }
+}
+
+mod choices {
+    use super::*;
+    use interchain_gov::state::{ChoiceRule, Choices, Governance, ProposalOption, Vote};
+
+    fn choices_proposal(env: &TestEnv<MockBech32>, action: ProposalAction) -> ProposalMsg {
+        let options = ["a", "b"]
+            .into_iter()
+            .map(|title| ProposalOption {
+                title: title.to_string(),
+                action: None,
+            })
+            .collect();
+        ProposalMsg {
+            choices: Some(Choices {
+                options,
+                rule: ChoiceRule::Plurality,
+            }),
+            ..test_proposal(
+                "choices",
+                action,
+                env.environment().block_info().unwrap().height + TEST_PROP_LEN,
+            )
+        }
+    }
+
+    #[test]
+    fn hash_commits_to_actions() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let signal = choices_proposal(&a_env, ProposalAction::Signal);
+        let pause = ProposalMsg {
+            choices: None,
+            action: ProposalAction::Pause {
+                reason: "paused".to_string(),
+            },
+            ..signal.clone()
+        };
+        let unpause = ProposalMsg {
+            action: ProposalAction::Unpause {},
+            ..pause.clone()
+        };
+        let mut other_options = signal.clone();
+        other_options.choices.as_mut().unwrap().options[0].action =
+            Some(ProposalAction::Unpause {});
+
+        let hashes = [&signal, &pause, &unpause, &other_options].map(ProposalMsg::hash);
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_that!(hashes[i + 1..].contains(hash)).is_false();
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn tally_many_votes() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let choices = choices_proposal(&a_env, ProposalAction::Signal)
+            .choices
+            .unwrap();
+        let votes = (0..600)
+            .map(|i| Vote::Choice(if i < 400 { 0 } else { 1 }))
+            .collect::<Vec<_>>();
+        let outcome = choices.tally(&votes);
+
+        assert_that!(outcome.tallies).is_equal_to(vec![400, 200]);
+        assert_that!(outcome.winner).is_equal_to(Some(0));
+        assert_that!(outcome.votes_for).is_equal_to(400);
+        assert_that!(outcome.votes_against).is_equal_to(200);
+
+        Ok(())
+    }
+
+    #[test]
+    fn options_replace_action() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let proposal = choices_proposal(
+            &a_env,
+            ProposalAction::Pause {
+                reason: "option".to_string(),
+            },
+        );
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal });
+        assert_that!(res).is_err();
+
+        Ok(())
+    }
+
+    #[test]
+    fn votes_select_option() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let proposal = choices_proposal(&a_env, ProposalAction::Signal);
+        let prop_id = proposal.hash();
+        a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal })?;
+
+        for vote in [Vote::Yes, Vote::Choice(2)] {
+            let res = a_env.execute_gov(InterchainGovExecuteMsg::VoteProposal {
+                prop_id: prop_id.clone(),
+                governance: Governance::Manual {},
+                vote,
+            });
+            assert_that!(res).is_err();
+        }
+        a_env.execute_gov(InterchainGovExecuteMsg::VoteProposal {
+            prop_id: prop_id.clone(),
+            governance: Governance::Manual {},
+            vote: Vote::Choice(1),
+        })?;
+        assert_that!(a_env.gov.vote(prop_id)?.vote.map(|vote| vote.vote))
+            .is_equal_to(Some(Vote::Choice(1)));
+
+        Ok(())
+    }
+}
+
+mod delegation {
+    use super::*;
+
+    #[test]
+    fn delegate_must_be_member() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Delegate {
+            delegate: ChainName::from_chain_id(B_CHAIN_ID),
+            actions: None,
+        });
+        assert_that!(res).is_err();
+        assert_that!(a_env.gov.delegations()?.delegations).is_empty();
+
+        Ok(())
+    }
+
+    #[test]
+    fn delegate_votes_for_absent_members() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Delegate {
+            delegate: b_env.chain_name(),
+            actions: None,
+        })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        // A counts with B's vote unless it votes itself, abstaining included
+        for a_vote in [None, Some(Vote::NoVote)] {
+            let title = format!("{a_vote:?}");
+            let (res, prop_id) = a_env.propose_proposal(&title, ProposalAction::Signal)?;
+            interchain.wait_ibc(A_CHAIN_ID, res)?;
+            let res = a_env.finalize_proposal(prop_id.clone())?;
+            interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+            if let Some(vote) = a_vote.clone() {
+                a_env
+                    .gov
+                    .vote_proposal(Governance::Manual {}, prop_id.clone(), vote)?;
+            }
+            b_env
+                .gov
+                .vote_proposal(Governance::Manual {}, prop_id.clone(), Vote::Yes)?;
+            a_env.wait_blocks(TEST_PROP_LEN + 1)?;
+            b_env.wait_blocks(TEST_PROP_LEN + 1)?;
+            let res = a_env.gov.request_vote_results(prop_id.clone())?;
+            interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+            let counted = a_env.gov.vote_results(prop_id)?.counted;
+            let a_counted = counted
+                .iter()
+                .find(|counted| counted.chain == a_env.chain_name())
+                .unwrap();
+            match a_vote {
+                None => {
+                    assert_that!(a_counted.vote).is_equal_to(Vote::Yes);
+                    assert_that!(a_counted.delegate).is_equal_to(Some(b_env.chain_name()));
+                }
+                Some(vote) => {
+                    assert_that!(a_counted.vote).is_equal_to(vote);
+                    assert_that!(a_counted.delegate).is_none();
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod pause {
+    use super::*;
+    use interchain_gov::state::{ChoiceRule, Choices, Guardians, ProposalOption};
+
+    #[test]
+    fn only_guardians_pause() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+
+        assert_that!(a_env.gov.status()?.pause).is_none();
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Pause {
+            reason: "not a guardian".to_string(),
+        });
+        assert_that!(res).is_err();
+        assert_that!(a_env.gov.status()?.pause).is_none();
+
+        Ok(())
+    }
+
+    #[test]
+    fn paused_options_must_unpause() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "pause",
+            ProposalAction::Pause {
+                reason: "paused".to_string(),
+            },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        assert_that!(a_env.gov.status()?.pause).is_some();
+
+        let height = a_env.environment().block_info()?.height;
+        let choices = |actions: Vec<Option<ProposalAction>>| ProposalMsg {
+            choices: Some(Choices {
+                options: actions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, action)| ProposalOption {
+                        title: i.to_string(),
+                        action,
+                    })
+                    .collect(),
+                rule: ChoiceRule::Plurality,
+            }),
+            ..test_proposal("choices", ProposalAction::Signal, height + TEST_PROP_LEN)
+        };
+
+        // An option that doesn't lift the pause can't progress while paused
+        let proposal = choices(vec![
+            Some(ProposalAction::Unpause {}),
+            Some(ProposalAction::UpdateGuardians {
+                guardians: Guardians::default(),
+            }),
+        ]);
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal });
+        assert_that!(res).is_err();
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::Propose {
+            proposal: choices(vec![None, None]),
+        });
+        assert_that!(res).is_err();
+
+        let proposal = choices(vec![Some(ProposalAction::Unpause {}), None]);
+        a_env.execute_gov(InterchainGovExecuteMsg::Propose { proposal })?;
+
+        Ok(())
+    }
+}
+
+mod upgrade {
+    use super::*;
+
+    #[test]
+    fn incompatible_packets_rejected() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let mut packet = InterchainGovIbcPacket::new(
+            a_env.gov.state_hash()?.hash,
+            InterchainGovIbcMsg::DelegationChanged { delegation: None },
+        );
+        packet.version = "99.0.0".to_string();
+        let err = deliver_packet(&b_env, a_env.chain_name(), &packet).unwrap_err();
+        assert_that!(format!("{err:?}")).contains("is incompatible with");
+
+        packet.version = ADAPTER_VERSION.to_string();
+        deliver_packet(&b_env, a_env.chain_name(), &packet)?;
+
+        Ok(())
+    }
+}
+
+mod treasury {
+    use super::*;
+    use cosmwasm_std::{coin, coins, Binary};
+    use interchain_gov::msg::{InterchainGovSudoMsg, RequestPacket};
+
+    #[test]
+    fn deposit_to_treasury() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![(A_CHAIN_ID, A_CHAIN_ADDR)]);
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        let a_env = TestEnv::setup(chain.clone())?;
+
+        let proxy = a_env.gov.account().proxy()?;
+        chain.add_balance(&proxy, coins(150, "untrn"))?;
+
+        a_env.execute_gov(InterchainGovExecuteMsg::DepositToTreasury {
+            funds: coins(100, "untrn"),
+        })?;
+        let balances = a_env.gov.treasury()?.balances;
+        assert_that!(balances).is_equal_to(vec![(a_env.chain_name(), vec![coin(100, "untrn")])]);
+
+        // Pledged funds stay on the account, it can't pledge more than it holds
+        assert_that!(chain.query_balance(&proxy, "untrn")?.u128()).is_equal_to(150);
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::DepositToTreasury {
+            funds: coins(100, "untrn"),
+        });
+        assert_that!(res).is_err();
+
+        // Other accounts using the adapter don't hold the treasury
+        let other = a_env
+            .abs
+            .account_builder()
+            .install_on_sub_account(false)
+            .build()?;
+        other.as_ref().manager.update_settings(Some(true))?;
+        other.install_adapter::<InterchainGovInterface<_>>(&[])?;
+        chain.add_balance(&other.proxy()?, coins(100, "untrn"))?;
+        let res = a_env.execute_gov_for(
+            InterchainGovExecuteMsg::DepositToTreasury {
+                funds: coins(100, "untrn"),
+            },
+            Some(&other),
+        );
+        assert_that!(res).is_err();
+
+        Ok(())
+    }
+
+    /// Pass and execute an ICS-20 transfer of 40 out of 100 deposited from A's treasury to B,
+    /// returns the recipient and the sent packet
+    fn execute_ibc_transfer(
+        interchain: &MockBech32InterchainEnv,
+        a_env: &TestEnv<MockBech32>,
+        b_env: &TestEnv<MockBech32>,
+        title: &str,
+    ) -> anyhow::Result<(ProposalId, Addr, RequestPacket)> {
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        let proxy = a_env.gov.account().proxy()?;
+        chain.add_balance(&proxy, coins(100, "untrn"))?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::DepositToTreasury {
+            funds: coins(100, "untrn"),
+        })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let recipient = interchain.chain(B_CHAIN_ID)?.addr_make("recipient");
+        let prop_id = vote_proposal(
+            interchain,
+            a_env,
+            b_env,
+            title,
+            ProposalAction::IbcTransfer {
+                from_chain: a_env.chain_name(),
+                to_chain: b_env.chain_name(),
+                recipient: recipient.to_string(),
+                amount: coins(40, "untrn"),
+            },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id.clone())?;
+        // The execution also sends the proposal result to B, pick the transfer's packet
+        let sent = res
+            .events
+            .iter()
+            .find(|event| {
+                event.ty == "send_packet"
+                    && event
+                        .attributes
+                        .iter()
+                        .any(|attr| attr.key == "packet_src_port" && attr.value == "transfer")
+            })
+            .unwrap();
+        let attr = |key: &str| {
+            sent.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.clone())
+        };
+        let packet = RequestPacket {
+            sequence: attr("packet_sequence").map(|seq| seq.parse()).transpose()?,
+            source_port: attr("packet_src_port"),
+            source_channel: attr("packet_src_channel"),
+            destination_port: attr("packet_dst_port"),
+            destination_channel: attr("packet_dst_channel"),
+            data: None,
+            timeout_height: None,
+            timeout_timestamp: None,
+        };
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        Ok((prop_id, recipient, packet))
+    }
+
+    /// Deliver the ack or timeout callback of a transfer to the adapter of A
+    fn transfer_callback(
+        interchain: &MockBech32InterchainEnv,
+        a_env: &TestEnv<MockBech32>,
+        msg: InterchainGovSudoMsg,
+    ) -> anyhow::Result<()> {
+        let res = interchain
+            .chain(A_CHAIN_ID)?
+            .app
+            .borrow_mut()
+            .wasm_sudo(a_env.gov.address()?, &msg)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        Ok(())
     }
 
-    fn finalize_proposal(&self, prop_id: ProposalId) -> anyhow::Result<Env::Response> {
-        let res = self.execute_gov(InterchainGovExecuteMsg::Finalize { prop_id })?;
+    #[test]
+    fn ibc_transfer_needs_callbacks() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        chain.add_balance(&a_env.gov.account().proxy()?, coins(100, "untrn"))?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::DepositToTreasury {
+            funds: coins(100, "untrn"),
+        })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
 
-        Ok(res)
+        // Juno never tells the adapter whether a transfer arrived
+        let transfer =
+            |from: &TestEnv<MockBech32>, to: &TestEnv<MockBech32>| ProposalAction::IbcTransfer {
+                from_chain: from.chain_name(),
+                to_chain: to.chain_name(),
+                recipient: to.gov.account().proxy().unwrap().to_string(),
+                amount: coins(40, "untrn"),
+            };
+        let err = a_env
+            .propose_proposal("from_juno", transfer(&b_env, &a_env))
+            .unwrap_err();
+        assert_that!(format!("{err:?}")).contains("doesn't report the result");
+        a_env.propose_proposal("from_neutron", transfer(&a_env, &b_env))?;
+        Ok(())
     }
 
-    fn assert_prop_state(
-        &self,
-        prop_id: ProposalId,
-        expected_state: Option<DataState>,
-    ) -> anyhow::Result<()> {
-        let state = self.gov.proposal_state(prop_id)?.map(|f| f.state);
-        assert_that!(state).is_equal_to(expected_state);
+    #[test]
+    fn ibc_transfer_executes_once() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+        connect_ics20(&interchain, &a_env)?;
+
+        let (prop_id, recipient, packet) =
+            execute_ibc_transfer(&interchain, &a_env, &b_env, "ibc_transfer_executes_once")?;
+
+        // Executing it again would send a second transfer
+        assert_that!(a_env.gov.execute_proposal(prop_id)).is_err();
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        let proxy = a_env.gov.account().proxy()?;
+        assert_that!(chain.query_balance(&proxy, "untrn")?.u128()).is_equal_to(60);
+        let received = interchain
+            .chain(B_CHAIN_ID)?
+            .query_all_balances(&recipient)?;
+        assert_that!(received).has_length(1);
+        assert_that!(received[0].amount.u128()).is_equal_to(40);
+        let balances = vec![(a_env.chain_name(), vec![coin(60, "untrn")])];
+        assert_that!(a_env.gov.treasury()?.balances).is_equal_to(balances.clone());
+        assert_that!(b_env.gov.treasury()?.balances).is_equal_to(balances.clone());
+
+        // The ack settles the transfer, only once
+        let ack = InterchainGovSudoMsg::Response {
+            request: packet,
+            data: Binary::default(),
+        };
+        transfer_callback(&interchain, &a_env, ack.clone())?;
+        assert_that!(transfer_callback(&interchain, &a_env, ack)).is_err();
+        assert_that!(a_env.gov.treasury()?.balances).is_equal_to(balances.clone());
+        assert_that!(b_env.gov.treasury()?.balances).is_equal_to(balances);
         Ok(())
     }
 
-    fn test_register_gov_modules(
-        &self,
-        govs: Vec<(ChainName, InterchainGovInterface<MockBech32>)>,
-    ) -> anyhow::Result<()> {
-        let modules = govs
-            .into_iter()
-            .map(|(chain_name, gov)| (chain_name, gov.address().unwrap().to_string()))
-            .collect();
-        self.execute_gov(
-            InterchainGovExecuteMsg::TemporaryRegisterRemoteGovModuleAddrs { modules },
+    #[test]
+    fn ibc_transfer_timeout_refunds() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+        connect_ics20(&interchain, &a_env)?;
+
+        let (_, _, packet) =
+            execute_ibc_transfer(&interchain, &a_env, &b_env, "ibc_transfer_timeout_refunds")?;
+
+        // ICS-20 refunds a timed out transfer to the adapter that sent it
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        chain.add_balance(&a_env.gov.address()?, coins(40, "untrn"))?;
+        transfer_callback(
+            &interchain,
+            &a_env,
+            InterchainGovSudoMsg::Timeout { request: packet },
         )?;
-        Ok(())
-    }
-}
 
-fn test_proposal(title: impl Into<String>, action: ProposalAction, exp_height: u64) -> ProposalMsg {
-    ProposalMsg {
-        title: title.into(),
-        description: "This is a test proposal".to_string(),
-        min_voting_period: None,
-        expiration: Expiration::AtHeight(exp_height),
-        action,
+        let proxy = a_env.gov.account().proxy()?;
+        assert_that!(chain.query_balance(&proxy, "untrn")?.u128()).is_equal_to(100);
+        let balances = vec![(a_env.chain_name(), vec![coin(100, "untrn")])];
+        assert_that!(a_env.gov.treasury()?.balances).is_equal_to(balances.clone());
+        assert_that!(b_env.gov.treasury()?.balances).is_equal_to(balances);
+        Ok(())
     }
 }
 
-mod propose {
-
+mod execute {
     use super::*;
+    use cosmwasm_std::{coin, coins};
 
     #[test]
-    fn happy_propose() -> anyhow::Result<()> {
+    fn executes_once() -> anyhow::Result<()> {
         let interchain = MockBech32InterchainEnv::new(vec![
             (A_CHAIN_ID, A_CHAIN_ADDR),
             (B_CHAIN_ID, B_CHAIN_ADDR),
         ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
 
-        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
-        let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        let proxy = a_env.gov.account().proxy()?;
+        chain.add_balance(&proxy, coins(100, "untrn"))?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::DepositToTreasury {
+            funds: coins(100, "untrn"),
+        })?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
 
-        a_env.enable_ibc()?;
-        b_env.enable_ibc()?;
-        ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+        let recipient = chain.addr_make("recipient");
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "executes_once",
+            ProposalAction::SpendFromTreasury {
+                chain: a_env.chain_name(),
+                recipient: recipient.to_string(),
+                amount: coins(40, "untrn"),
+            },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id.clone())?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
 
-        let a_gov = a_env.gov.clone();
-        let b_gov = b_env.gov.clone();
+        // Executing it again would spend the funds twice
+        assert_that!(a_env.gov.execute_proposal(prop_id)).is_err();
+        assert_that!(chain.query_balance(&recipient, "untrn")?.u128()).is_equal_to(40);
+        let balances = vec![(a_env.chain_name(), vec![coin(60, "untrn")])];
+        assert_that!(a_env.gov.treasury()?.balances).is_equal_to(balances.clone());
+        assert_that!(b_env.gov.treasury()?.balances).is_equal_to(balances);
+        Ok(())
+    }
 
-        a_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
-            members: vec![b_env.chain_name(), a_env.chain_name()].into(),
-        })?;
+    #[test]
+    fn only_proposer_reports_outcome() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
 
-        b_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
-            members: vec![a_env.chain_name(), b_env.chain_name()].into(),
+        let chain = interchain.chain(A_CHAIN_ID)?;
+        let proxy = a_env.gov.account().proxy()?;
+        chain.add_balance(&proxy, coins(100, "untrn"))?;
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::DepositToTreasury {
+            funds: coins(100, "untrn"),
         })?;
-
-        // Create proposal
-        let (res, prop_id) = a_env.propose_proposal("happy_propose", ProposalAction::Signal)?;
-
         interchain.wait_ibc(A_CHAIN_ID, res)?;
 
-        let proposals = a_gov.list_proposal_states()?;
-        assert_that!(proposals.state.len()).is_equal_to(0);
-
-        let proposals = b_gov.list_proposal_states()?;
-        assert_that!(proposals.state.len()).is_equal_to(1);
-
-        // check the state.
-        // should be proposed on A and pending on B
-        a_env.assert_prop_state(prop_id.clone(), None)?;
-        b_env.assert_prop_state(prop_id, Some(DataState::Proposed))?;
+        let recipient = chain.addr_make("recipient");
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "only_proposer_reports_outcome",
+            ProposalAction::SpendFromTreasury {
+                chain: a_env.chain_name(),
+                recipient: recipient.to_string(),
+                amount: coins(40, "untrn"),
+            },
+            Vote::No,
+        )?;
 
+        // B can't execute A's proposal, nor report a made up outcome to A
+        assert_that!(b_env.gov.execute_proposal(prop_id.clone())).is_err();
+        let result = InterchainGovIbcPacket::new(
+            a_env.gov.state_hash()?.hash,
+            InterchainGovIbcMsg::ProposalResult {
+                prop_hash: prop_id,
+                outcome: ProposalOutcome {
+                    passed: true,
+                    votes_for: 2,
+                    votes_against: 0,
+                    no_votes: 0,
+                    action_result: None,
+                    vetoed: None,
+                    tallies: vec![],
+                    winner: None,
+                },
+            },
+        );
+        assert_that!(deliver_packet(&a_env, b_env.chain_name(), &result)).is_err();
+        // Neither can a chain outside of the governance
+        assert_that!(deliver_packet(
+            &a_env,
+            ChainName::from_chain_id(C_CHAIN_ID),
+            &result
+        ))
+        .is_err();
+
+        assert_that!(chain.query_balance(&recipient, "untrn")?.u128()).is_equal_to(0);
+        assert_that!(chain.query_balance(&proxy, "untrn")?.u128()).is_equal_to(100);
         Ok(())
     }
 }
@@ -337,7 +1491,7 @@ mod query_vote_results {
             vote: Vote::Yes,
         })?;
         let a_vote = a_gov.vote(prop_id.clone())?;
-        assert_that!(a_vote.vote).is_equal_to(Vote::Yes);
+        assert_that!(a_vote.vote.map(|vote| vote.vote)).is_equal_to(Some(Vote::Yes));
 
         b_env.execute_gov(InterchainGovExecuteMsg::VoteProposal {
             prop_id: prop_id.clone(),
@@ -345,7 +1499,7 @@ mod query_vote_results {
             vote: Vote::Yes,
         })?;
         let vote = b_gov.vote(prop_id.clone())?;
-        assert_that!(vote.vote).is_equal_to(Vote::Yes);
+        assert_that!(vote.vote.map(|vote| vote.vote)).is_equal_to(Some(Vote::Yes));
 
         // Wait the test blocks after voting
         a_env.wait_blocks(TEST_PROP_LEN + 1)?;
@@ -383,8 +1537,8 @@ mod finalize {
         b_env.enable_ibc()?;
         ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
 
-        let _a_gov = a_env.gov.clone();
-        let _b_gov = b_env.gov.clone();
+        let a_gov = a_env.gov.clone();
+        let b_gov = b_env.gov.clone();
 
         a_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
             members: vec![b_env.chain_name(), a_env.chain_name()].into(),
@@ -406,7 +1560,21 @@ mod finalize {
         let _analysis = interchain.wait_ibc(A_CHAIN_ID, res)?;
         // TODO: this errs
         a_env.assert_prop_state(prop_id.clone(), None)?;
-        b_env.assert_prop_state(prop_id, None)?;
+        b_env.assert_prop_state(prop_id.clone(), None)?;
+
+        // The member's history shows the proposal being prepared and committed
+        let actions = b_gov
+            .proposal_history(prop_id.clone())?
+            .events
+            .into_iter()
+            .map(|event| event.action)
+            .collect::<Vec<_>>();
+        assert_eq!(actions, vec![SyncAction::Propose, SyncAction::Finalize]);
+
+        // Both committed the proposal on top of the same state
+        let a_hash = a_gov.state_hash()?.hash;
+        assert_that!(a_hash.is_empty()).is_false();
+        assert_eq!(a_hash, b_gov.state_hash()?.hash);
 
         Ok(())
     }
@@ -467,7 +1635,7 @@ mod members {
     #[test]
     fn happy_member_add() -> anyhow::Result<()> {
         env::set_var("RUST_LOG", "debug");
-        env_logger::init();
+        let _ = env_logger::try_init();
 
         let interchain = MockBech32InterchainEnv::new(vec![
             (A_CHAIN_ID, A_CHAIN_ADDR),
@@ -480,6 +1648,7 @@ mod members {
         a_env.enable_ibc()?;
         b_env.enable_ibc()?;
         ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+        ibc_connect_abstract(&interchain, B_CHAIN_ID, A_CHAIN_ID)?;
 
         let a_gov = a_env.gov.clone();
         let b_gov = b_env.gov.clone();
@@ -496,17 +1665,34 @@ mod members {
         a_env.assert_prop_state(prop_id.clone(), None)?;
         b_env.assert_prop_state(prop_id.clone(), None)?;
 
+        // B is invited but not a member until it accepts
+        let invites = b_gov.pending_invites()?.invites;
+        assert_that!(invites).has_length(1);
+        assert_eq!(invites[0].0, a_env.chain_name());
+        let b_members = b_gov.members()?;
+        assert_eq!(b_members.members.members, vec![b_env.chain_name()]);
+
+        let res = b_gov.accept_invite(a_env.chain_name())?;
+        interchain.wait_ibc(B_CHAIN_ID, res)?;
+        assert_that!(b_gov.pending_invites()?.invites).is_empty();
+
         let a_members = dbg!(a_gov.members()?);
         assert_eq!(a_members.members.members.len(), 2);
         let b_members = dbg!(b_gov.members()?);
         assert_eq!(b_members.members.members.len(), 2);
+        assert_eq!(a_gov.state_hash()?.hash, b_gov.state_hash()?.hash);
+
+        // Joining started a new membership epoch, the old members are still available
+        assert_eq!(a_members.epoch, 1);
+        let a_members_before = a_gov.members_at(0)?;
+        assert_eq!(a_members_before.members.members, vec![a_env.chain_name()]);
         Ok(())
     }
 
     #[test]
     fn happy_member_add_3() -> anyhow::Result<()> {
         env::set_var("RUST_LOG", "info");
-        env_logger::init();
+        let _ = env_logger::try_init();
 
         let interchain = MockBech32InterchainEnv::new(vec![
             (A_CHAIN_ID, A_CHAIN_ADDR),
@@ -552,6 +1738,9 @@ mod members {
         let res = interchain.wait_ibc(A_CHAIN_ID, res)?;
         dbg!(&res.packets[0].outcome);
 
+        let res = b_gov.accept_invite(a_env.chain_name())?;
+        interchain.wait_ibc(B_CHAIN_ID, res)?;
+
         let a_members = dbg!(a_gov.members()?);
         assert_eq!(a_members.members.members.len(), 2);
         let b_members = dbg!(b_gov.members()?);
@@ -617,14 +1806,281 @@ mod members {
 
         let res = a_gov.execute_proposal(prop_id.clone())?;
 
+        // C is only a member once it accepted
         let a_members = dbg!(a_gov.members()?);
-        assert_eq!(a_members.members.members.len(), 3);
+        assert_eq!(a_members.members.members.len(), 2);
 
         let res = interchain.wait_ibc(A_CHAIN_ID, res)?;
         dbg!(&res.packets[0].outcome);
+        let b_members = b_gov.members()?;
+        assert_eq!(b_members.members.members.len(), 2);
+
+        let res = c_gov.accept_invite(a_env.chain_name())?;
+        interchain.wait_ibc(C_CHAIN_ID, res)?;
+
+        for gov in [&a_gov, &b_gov, &c_gov] {
+            let members = gov.members()?;
+            assert_eq!(members.members.members.len(), 3);
+        }
+
+        // C continues from the governance's state
+        let a_hash = a_gov.state_hash()?.hash;
+        assert_eq!(a_hash, b_gov.state_hash()?.hash);
+        assert_eq!(a_hash, c_gov.state_hash()?.hash);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invite() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+        let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+
+        a_env.enable_ibc()?;
+        b_env.enable_ibc()?;
+        ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+        ibc_connect_abstract(&interchain, B_CHAIN_ID, A_CHAIN_ID)?;
+
+        let a_gov = a_env.gov.clone();
+        let b_gov = b_env.gov.clone();
+
+        let (res, _) = a_env.propose_first_member_proposal(
+            "reject_invite",
+            ProposalAction::UpdateMembers {
+                members: vec![b_env.chain_name(), a_env.chain_name()].into(),
+            },
+        )?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let res = b_gov.reject_invite(a_env.chain_name())?;
+        interchain.wait_ibc(B_CHAIN_ID, res)?;
+
+        // Neither chain joined the other
+        assert_that!(b_gov.pending_invites()?.invites).is_empty();
+        let a_members = a_gov.members()?;
+        assert_eq!(a_members.members.members, vec![a_env.chain_name()]);
+        let b_members = b_gov.members()?;
+        assert_eq!(b_members.members.members, vec![b_env.chain_name()]);
+
+        // The invite can't be accepted anymore
+        assert_that!(b_gov.accept_invite(a_env.chain_name())).is_err();
+        Ok(())
+    }
+
+    #[test]
+    fn rejected_removal_keeps_member() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "rejected_removal_keeps_member",
+            ProposalAction::RemoveMember {
+                member: b_env.chain_name(),
+            },
+            Vote::No,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let members = vec![a_env.chain_name(), b_env.chain_name()];
+        assert_eq!(a_env.gov.members()?.members.members, members);
+        assert_eq!(b_env.gov.members()?.members.members, members);
+        assert_eq!(a_env.gov.state_hash()?.hash, b_env.gov.state_hash()?.hash);
+        Ok(())
+    }
+
+    #[test]
+    fn passed_removal_removes_member() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+        let (a_env, b_env) = setup_governance(&interchain)?;
+
+        let prop_id = vote_proposal(
+            &interchain,
+            &a_env,
+            &b_env,
+            "passed_removal_removes_member",
+            ProposalAction::RemoveMember {
+                member: b_env.chain_name(),
+            },
+            Vote::Yes,
+        )?;
+        let res = a_env.gov.execute_proposal(prop_id.clone())?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        assert_eq!(
+            a_env.gov.members()?.members.members,
+            vec![a_env.chain_name()]
+        );
+        // B checked the outcome it was sent before leaving
+        assert_eq!(
+            b_env.gov.members()?.members.members,
+            vec![b_env.chain_name()]
+        );
+        assert_that!(b_env.gov.proposal_hashes()?.executed).contains(prop_id);
+        Ok(())
+    }
+
+    #[test]
+    fn invite_partly_rejected() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+            (C_CHAIN_ID, C_CHAIN_ADDR),
+        ]);
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+        let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+        let c_env = TestEnv::setup(interchain.chain(C_CHAIN_ID)?)?;
+        for env in [&a_env, &b_env, &c_env] {
+            env.enable_ibc()?;
+        }
+        for (from, to) in [
+            (A_CHAIN_ID, B_CHAIN_ID),
+            (A_CHAIN_ID, C_CHAIN_ID),
+            (B_CHAIN_ID, A_CHAIN_ID),
+            (B_CHAIN_ID, C_CHAIN_ID),
+            (C_CHAIN_ID, A_CHAIN_ID),
+            (C_CHAIN_ID, B_CHAIN_ID),
+        ] {
+            ibc_connect_abstract(&interchain, from, to)?;
+        }
+        let all_members: Members =
+            vec![a_env.chain_name(), b_env.chain_name(), c_env.chain_name()].into();
+        b_env.gov.set_accept_gov_invite(all_members.clone())?;
+        c_env.gov.set_accept_gov_invite(all_members.clone())?;
+
+        let (res, _) = a_env.propose_first_member_proposal(
+            "invite_partly_rejected",
+            ProposalAction::UpdateMembers {
+                members: all_members,
+            },
+        )?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        // C's answer reaches B before B answered its own invite
+        let res = c_env.gov.reject_invite(a_env.chain_name())?;
+        interchain.wait_ibc(C_CHAIN_ID, res)?;
+        assert_eq!(
+            a_env.gov.members()?.members.members,
+            vec![a_env.chain_name()]
+        );
+
+        let res = b_env.gov.accept_invite(a_env.chain_name())?;
+        interchain.wait_ibc(B_CHAIN_ID, res)?;
+
+        // Only the chains that accepted are members, committed alike on both
+        let members = vec![a_env.chain_name(), b_env.chain_name()];
+        assert_eq!(a_env.gov.members()?.members.members, members);
+        assert_eq!(b_env.gov.members()?.members.members, members);
+        assert_eq!(
+            c_env.gov.members()?.members.members,
+            vec![c_env.chain_name()]
+        );
+        assert_eq!(a_env.gov.state_hash()?.hash, b_env.gov.state_hash()?.hash);
+        Ok(())
+    }
+
+    #[test]
+    fn sync_check() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+        let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+
+        a_env.enable_ibc()?;
+        b_env.enable_ibc()?;
+        ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+        a_env.test_register_gov_modules(vec![(b_env.chain_name(), b_env.gov.clone())])?;
+
+        let a_gov = a_env.gov.clone();
+
+        a_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![b_env.chain_name(), a_env.chain_name()].into(),
+        })?;
+        b_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![b_env.chain_name(), a_env.chain_name()].into(),
+        })?;
+
+        // In sync
+        let res = a_gov.sync_check()?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        let reports = a_gov.sync_reports()?;
+        assert_that!(reports.diverged).is_empty();
+        assert_that!(reports.pending).is_empty();
+
+        // B thinks C is a member
+        let c_chain = ChainName::from_chain_id(C_CHAIN_ID);
+        b_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![b_env.chain_name(), a_env.chain_name(), c_chain.clone()].into(),
+        })?;
+
+        let res = a_gov.sync_check()?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+        let reports = a_gov.sync_reports()?;
+        assert_that!(reports.diverged).has_length(1);
+        let (chain, report) = &reports.diverged[0];
+        assert_eq!(chain, &b_env.chain_name());
+        let members_diff = report.members.clone().unwrap();
+        assert_that!(members_diff.missing).is_empty();
+        assert_eq!(members_diff.unexpected, vec![c_chain]);
+        assert!(report.proposals.clone().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn happy_leave() -> anyhow::Result<()> {
+        let interchain = MockBech32InterchainEnv::new(vec![
+            (A_CHAIN_ID, A_CHAIN_ADDR),
+            (B_CHAIN_ID, B_CHAIN_ADDR),
+        ]);
+
+        let a_env = TestEnv::setup(interchain.chain(A_CHAIN_ID)?)?;
+        let b_env = TestEnv::setup(interchain.chain(B_CHAIN_ID)?)?;
+
+        a_env.enable_ibc()?;
+        b_env.enable_ibc()?;
+        ibc_connect_polytone_and_abstract(&interchain, A_CHAIN_ID, B_CHAIN_ID)?;
+
+        let a_gov = a_env.gov.clone();
+        let b_gov = b_env.gov.clone();
+
+        a_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![b_env.chain_name(), a_env.chain_name()].into(),
+        })?;
+        b_env.execute_gov(InterchainGovExecuteMsg::TestAddMembers {
+            members: vec![a_env.chain_name(), b_env.chain_name()].into(),
+        })?;
+
+        // Open a proposal that A will abandon
+        let (res, prop_id) = a_env.propose_proposal("happy_leave", ProposalAction::Signal)?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let res = a_env.execute_gov(InterchainGovExecuteMsg::LeaveGovernance {})?;
+        interchain.wait_ibc(A_CHAIN_ID, res)?;
+
+        let a_members = a_gov.members()?;
+        assert_eq!(a_members.members.members, vec![a_env.chain_name()]);
+        let b_members = b_gov.members()?;
+        assert_eq!(b_members.members.members, vec![b_env.chain_name()]);
 
-        let c_members = dbg!(c_gov.members()?);
-        assert_eq!(c_members.members.members.len(), 3);
+        // A dropped the open proposal
+        a_env.assert_prop_state(prop_id, None)?;
+        assert_that!(a_gov.list_proposals()?.proposals).is_empty();
         Ok(())
     }
 }
@@ -0,0 +1,218 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{Addr, OwnedDeps, Storage};
+use cw_utils::Expiration;
+use interchain_gov::contract::migrate;
+use interchain_gov::msg::InterchainGovMigrateMsg;
+use interchain_gov::state::{
+    GovConfig, Governance, GovernanceVote, Members, Proposal, ProposalAction, ProposalMsg, Vote,
+    VoteResult, FINALIZED_PROPOSALS, GOVERNING_ACCOUNT, MEMBERSHIP_EPOCH, MEMBERS_AT,
+    MEMBERS_STATE_SYNC, PENDING_QUERIES, PENDING_REPLIES, PROPOSAL_STATE_SYNC, VOTE_RESULTS,
+};
+use interchain_gov::{InterchainGovError, ADAPTER_VERSION, MY_ADAPTER_ID};
+use speculoos::prelude::*;
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+/// Members of the 0.0.1 fixtures
+const MEMBERS_FIXTURE: &str = r#"{"members":["cosmos","juno"]}"#;
+/// `Proposal` as stored by 0.0.1, without epoch and members
+const PROPOSAL_FIXTURE: &str = r#"{"title":"old","description":"Stored by 0.0.1","proposer":"proposer","proposer_chain":"cosmos","action":"signal","min_voting_period":null,"expiration":{"at_height":12445},"threshold":{"absolute_percentage":{"percentage":{"majority":{}}}}}"#;
+
+/// Storage of an adapter instantiated at `version`, with the members in the 0.0.1 layout
+fn stored_at(version: &str) -> MockDeps {
+    let mut deps = mock_dependencies();
+    cw2::set_contract_version(&mut deps.storage, MY_ADAPTER_ID, version).unwrap();
+    set_fixture(&mut deps.storage, b"members", MEMBERS_FIXTURE);
+    set_fixture(&mut deps.storage, b"acks", "[]");
+    set_fixture(&mut deps.storage, b"map_acks", r#"["juno"]"#);
+    deps
+}
+
+/// Raw value as written by an older version
+fn set_fixture(storage: &mut dyn Storage, key: &[u8], json: &str) {
+    storage.set(key, json.as_bytes());
+}
+
+/// Raw key of `key` in the map stored under `namespace`
+fn map_key(namespace: &str, key: &str) -> Vec<u8> {
+    let mut raw = (namespace.len() as u16).to_be_bytes().to_vec();
+    raw.extend(namespace.as_bytes());
+    raw.extend(key.as_bytes());
+    raw
+}
+
+/// Migration of an adapter governing the account with the `proxy` proxy
+fn migrate_msg() -> InterchainGovMigrateMsg {
+    InterchainGovMigrateMsg {
+        account: Some("proxy".to_string()),
+    }
+}
+
+fn fixture_members() -> Members {
+    vec![
+        ChainName::from_string("cosmos".to_string()).unwrap(),
+        ChainName::from_string("juno".to_string()).unwrap(),
+    ]
+    .into()
+}
+
+#[test]
+fn migrates_proposals_of_0_0_1() {
+    let mut deps = stored_at("0.0.1");
+    set_fixture(
+        &mut deps.storage,
+        &map_key("props", "pending"),
+        &format!(r#"[{PROPOSAL_FIXTURE},"yes"]"#),
+    );
+    set_fixture(
+        &mut deps.storage,
+        &map_key("finalized_props", "done"),
+        &format!(r#"[{PROPOSAL_FIXTURE},{{"passed":true,"votes_for":2,"votes_against":0}}]"#),
+    );
+
+    migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+    let members = fixture_members();
+    let (prop, vote) = PROPOSAL_STATE_SYNC
+        .load(&deps.storage, "pending".to_string())
+        .unwrap();
+    assert_that!(vote).is_equal_to(Vote::Yes);
+    assert_that!(prop.epoch).is_equal_to(0);
+    assert_that!(prop.members).is_equal_to(members.clone());
+    assert_that!(prop.title.as_str()).is_equal_to("old");
+
+    let (prop, outcome) = FINALIZED_PROPOSALS
+        .load(&deps.storage, "done".to_string())
+        .unwrap();
+    assert_that!(prop.members).is_equal_to(members);
+    assert_that!(outcome.passed).is_true();
+    assert_that!(outcome.votes_for).is_equal_to(2);
+    assert_that!(cw2::get_contract_version(&deps.storage).unwrap().version)
+        .is_equal_to(ADAPTER_VERSION.to_string());
+}
+
+#[test]
+fn seeds_membership_epoch_of_0_0_1() {
+    let mut deps = stored_at("0.0.1");
+
+    migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+    let members = fixture_members();
+    assert_that!(MEMBERSHIP_EPOCH.load(&deps.storage).unwrap()).is_equal_to(0);
+    assert_that!(MEMBERS_AT.load(&deps.storage, 0).unwrap()).is_equal_to(members.clone());
+    assert_that!(MEMBERS_STATE_SYNC.load_members(&deps.storage).unwrap()).is_equal_to(members);
+    assert_that!(deps.storage.get(b"acks")).is_none();
+    assert_that!(deps.storage.get(b"map_acks")).is_none();
+
+    // The next change starts a new epoch on top of the seeded one
+    MEMBERS_STATE_SYNC
+        .save_members(&mut deps.storage, &Members::new(&mock_env()))
+        .unwrap();
+    assert_that!(MEMBERSHIP_EPOCH.load(&deps.storage).unwrap()).is_equal_to(1);
+}
+
+#[test]
+fn sets_governing_account_of_0_0_1() {
+    let mut deps = stored_at("0.0.1");
+
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        InterchainGovMigrateMsg { account: None },
+    );
+    assert_that!(res).is_err_containing(InterchainGovError::NoGoverningAccount);
+
+    migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+    assert_that!(GOVERNING_ACCOUNT.load(&deps.storage).unwrap())
+        .is_equal_to(Addr::unchecked("proxy"));
+}
+
+#[test]
+fn moves_pending_queries_of_0_0_1() {
+    let mut deps = stored_at("0.0.1");
+    // Query ids are stored as big endian u64 keys
+    let key = [map_key("pending_replies", ""), 2u64.to_be_bytes().to_vec()].concat();
+    set_fixture(&mut deps.storage, &key, r#"["juno","prop"]"#);
+
+    migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+    assert_that!(deps.storage.get(&key)).is_none();
+    assert_that!(PENDING_QUERIES.load(&deps.storage, 2).unwrap()).is_equal_to((
+        ChainName::from_string("juno".to_string()).unwrap(),
+        "prop".to_string(),
+    ));
+    assert_that!(PENDING_REPLIES.is_empty(&deps.storage)).is_true();
+}
+
+#[test]
+fn migrates_vote_results_of_0_0_1() {
+    let mut deps = stored_at("0.0.1");
+    // Composite keys prefix the proposal id with its length
+    let result_key = |chain: &str| {
+        let mut key = map_key("vote_results", "");
+        key.extend(("prop".len() as u16).to_be_bytes());
+        key.extend(b"prop");
+        key.extend(chain.as_bytes());
+        key
+    };
+    set_fixture(
+        &mut deps.storage,
+        &result_key("cosmos"),
+        r#"{"vote":"yes","governance":{"manual":{}}}"#,
+    );
+    set_fixture(&mut deps.storage, &result_key("juno"), "null");
+
+    migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+    let [cosmos, juno] = fixture_members().members.try_into().unwrap();
+    assert_that!(VOTE_RESULTS
+        .load(&deps.storage, ("prop".to_string(), &cosmos))
+        .unwrap())
+    .is_equal_to(VoteResult::Cast(GovernanceVote::new(
+        Governance::Manual {},
+        Vote::Yes,
+    )));
+    assert_that!(VOTE_RESULTS
+        .load(&deps.storage, ("prop".to_string(), &juno))
+        .unwrap())
+    .is_equal_to(VoteResult::Pending);
+}
+
+#[test]
+fn rejects_same_version() {
+    let mut deps = stored_at(ADAPTER_VERSION);
+
+    let res = migrate(deps.as_mut(), mock_env(), migrate_msg());
+    assert_that!(res).is_err();
+}
+
+#[test]
+fn rejects_migration_while_proposal_syncing() {
+    let mut deps = stored_at("0.0.1");
+    let env = mock_env();
+    let prop = Proposal::new(
+        ProposalMsg {
+            title: "syncing".to_string(),
+            description: "Proposed but not finalized".to_string(),
+            min_voting_period: None,
+            expiration: Expiration::AtHeight(env.block.height + 100),
+            action: ProposalAction::Signal,
+            emergency: false,
+            choices: None,
+        },
+        &Addr::unchecked("proposer"),
+        &env,
+        0,
+        Members::new(&env),
+        &GovConfig::default(),
+    );
+    PROPOSAL_STATE_SYNC
+        .propose_kv_state(&mut deps.storage, "prop".to_string(), (prop, Vote::NoVote))
+        .unwrap();
+
+    let res = migrate(deps.as_mut(), env, migrate_msg());
+    assert_that!(res).is_err_containing(InterchainGovError::MigrateWhileSyncing {
+        what: "1 proposals".to_string(),
+    });
+}
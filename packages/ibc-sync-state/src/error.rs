@@ -1,5 +1,7 @@
 use cosmwasm_std::StdError;
 
+use crate::{DataState, Key, SyncId};
+
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -7,12 +9,34 @@ pub enum SyncStateError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("Data {key} not found")]
+    NotFound { key: Key },
+
     #[error("Data {key} not finalized. Status: {state}")]
-    DataNotFinalized { key: String, state: String },
+    DataNotFinalized { key: Key, state: DataState },
 
     #[error("No proposed state for state transition")]
     NoProposedState,
 
-    #[error("Data {key} not proposed. Status: {state}")]
-    DataNotProposed { key: String, state: String },
+    /// `state` is `None` when the data is finalized
+    #[error("Data {key} not proposed. Status: {state:?}")]
+    DataNotProposed { key: Key, state: Option<DataState> },
+
+    #[error("Batch {sync_id} already exists")]
+    BatchExists { sync_id: SyncId },
+
+    #[error("No batch {sync_id}")]
+    UnknownBatch { sync_id: SyncId },
+
+    #[error("Transaction {key} is still pending")]
+    TransactionPending { key: String },
+
+    #[error("No transaction for {key}")]
+    UnknownTransaction { key: String },
+
+    #[error("{chain} is not expected to answer for {key}")]
+    UnexpectedParticipant { key: String, chain: String },
+
+    #[error("Transaction {key} is in phase {phase}")]
+    InvalidPhase { key: String, phase: String },
 }
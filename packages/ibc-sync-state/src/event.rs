@@ -0,0 +1,117 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Attribute, StdResult, Storage};
+use cw_storage_plus::Map;
+
+use crate::{DataState, Key, StorageKey};
+
+#[cw_serde]
+pub enum SyncAction {
+    Initiate,
+    Propose,
+    Finalize,
+    Revert,
+    Ack,
+}
+
+/// A state transition of a synced value
+#[cw_serde]
+pub struct SyncEvent {
+    /// Namespace of the synced map or item
+    pub namespace: String,
+    /// Key in the map, sync id for batch acks, `None` for items and map-wide acks
+    pub key: Option<Key>,
+    pub action: SyncAction,
+    /// State before the transition, `None` when finalized
+    pub from: Option<DataState>,
+    /// State after the transition, `None` when finalized
+    pub to: Option<DataState>,
+    /// Chain that acked
+    pub chain: Option<ChainName>,
+}
+
+impl SyncEvent {
+    pub(crate) fn new(
+        namespace: StorageKey,
+        key: Option<Key>,
+        action: SyncAction,
+        from: Option<DataState>,
+        to: Option<DataState>,
+    ) -> Self {
+        SyncEvent {
+            namespace,
+            key,
+            action,
+            from,
+            to,
+            chain: None,
+        }
+    }
+
+    pub(crate) fn with_chain(mut self, chain: ChainName) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// Attributes to attach to the response of the handler that caused the transition
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let state = |state: &Option<DataState>| {
+            state
+                .as_ref()
+                .map_or("Finalized".to_string(), ToString::to_string)
+        };
+
+        let mut attributes = vec![
+            Attribute::new("sync_action", format!("{:?}", self.action)),
+            Attribute::new("sync_namespace", &self.namespace),
+        ];
+        if let Some(key) = &self.key {
+            attributes.push(Attribute::new("sync_key", key));
+        }
+        attributes.push(Attribute::new("sync_from", state(&self.from)));
+        attributes.push(Attribute::new("sync_to", state(&self.to)));
+        if let Some(chain) = &self.chain {
+            attributes.push(Attribute::new("sync_chain", chain.to_string()));
+        }
+        attributes
+    }
+}
+
+/// The last `limit` events of each key
+pub(crate) struct SyncHistory {
+    log: Map<'static, (StorageKey, Key), Vec<SyncEvent>>,
+    limit: u32,
+}
+
+impl SyncHistory {
+    pub(crate) const fn new(namespace: &'static str, limit: u32) -> Self {
+        SyncHistory {
+            log: Map::new(namespace),
+            limit,
+        }
+    }
+
+    pub(crate) fn record(&self, storage: &mut dyn Storage, event: &SyncEvent) -> StdResult<()> {
+        let key = (
+            event.namespace.clone(),
+            event.key.clone().unwrap_or_default(),
+        );
+        let mut events = self.log.may_load(storage, key.clone())?.unwrap_or_default();
+        events.push(event.clone());
+        let overflow = events.len().saturating_sub(self.limit as usize);
+        events.drain(..overflow);
+        self.log.save(storage, key, &events)
+    }
+
+    pub(crate) fn load(
+        &self,
+        storage: &dyn Storage,
+        namespace: StorageKey,
+        key: Key,
+    ) -> StdResult<Vec<SyncEvent>> {
+        Ok(self
+            .log
+            .may_load(storage, (namespace, key))?
+            .unwrap_or_default())
+    }
+}
@@ -1,8 +1,14 @@
-use cosmwasm_std::Storage;
-use cw_storage_plus::Map;
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::{from_json, to_json_binary, StdResult, Storage};
+use cw_storage_plus::{Item, Map};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::error::SyncStateError;
-use crate::{DataState, StateChange, StorageKey, SyncStateResult};
+use crate::event::SyncHistory;
+use crate::{
+    DataState, Key, StateChange, StorageKey, SyncAction, SyncEvent, SyncStateResult, BACKUP_SLOT,
+    ITEM_ACKS_NAMESPACE, ITEM_DATA_NAMESPACE,
+};
 
 /// LOCAL
 /// Instantiate: members ([A]) -> DNE
@@ -12,115 +18,297 @@ use crate::{DataState, StateChange, StorageKey, SyncStateResult};
 /// REMOTE
 /// Instantiate: members ([B]) -> DNE
 /// Proposal received: members([A, B]) -> proposed, Backup([A])
-const ITEMS_DATA_STATE: Map<(StorageKey, u8), StateChange> = Map::new("item_data");
+pub const ITEMS_DATA_STATE: Map<(StorageKey, u8), StateChange> = Map::new(ITEM_DATA_NAMESPACE);
+/// Outstanding acks per item, keyed by the namespace of the item
+pub const ITEMS_OUTSTANDING_ACKS: Map<StorageKey, Vec<ChainName>> = Map::new(ITEM_ACKS_NAMESPACE);
 
-/// Local members to local data status
-/// Remote member statuses
-
-pub struct ItemStateSyncController {
+/// Syncs a singleton value across chains, the `Item` counterpart of [`crate::MapStateSyncController`].
+pub struct ItemStateSyncController<'a, T> {
     state_status_map: Map<'static, (StorageKey, u8), StateChange>,
+    item: Item<'a, T>,
+    outstanding_acks: Map<'static, StorageKey, Vec<ChainName>>,
+    history: Option<SyncHistory>,
 }
 
-impl ItemStateSyncController {
-    pub const fn new() -> Self {
+impl<'a, T> ItemStateSyncController<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Controller using the default namespaces, shared with all other item controllers
+    pub const fn new(item: Item<'a, T>) -> Self {
         ItemStateSyncController {
             state_status_map: ITEMS_DATA_STATE,
+            item,
+            outstanding_acks: ITEMS_OUTSTANDING_ACKS,
+            history: None,
+        }
+    }
+
+    /// Controller that stores its data states and outstanding acks under its own namespaces
+    pub const fn with_namespaces(
+        item: Item<'a, T>,
+        data_namespace: &'static str,
+        acks_namespace: &'static str,
+    ) -> Self {
+        ItemStateSyncController {
+            state_status_map: Map::new(data_namespace),
+            item,
+            outstanding_acks: Map::new(acks_namespace),
+            history: None,
+        }
+    }
+
+    /// Keep the last `limit` events of the item under the namespace
+    pub const fn with_history(self, namespace: &'static str, limit: u32) -> Self {
+        ItemStateSyncController {
+            history: Some(SyncHistory::new(namespace, limit)),
+            ..self
+        }
+    }
+
+    pub const fn item(&self) -> &Item<'a, T> {
+        &self.item
+    }
+
+    fn storage_key(&self) -> StorageKey {
+        std::str::from_utf8(self.item.as_slice())
+            .unwrap()
+            .to_string()
+    }
+
+    fn event(
+        &self,
+        action: SyncAction,
+        from: Option<DataState>,
+        to: Option<DataState>,
+    ) -> SyncEvent {
+        SyncEvent::new(self.storage_key(), None, action, from, to)
+    }
+
+    /// Record the event in the history, if kept
+    fn emit(&self, storage: &mut dyn Storage, event: SyncEvent) -> SyncStateResult<SyncEvent> {
+        if let Some(history) = &self.history {
+            history.record(storage, &event)?;
+        }
+        Ok(event)
+    }
+
+    /// Last events of the item, empty if no history is kept
+    pub fn history(&self, storage: &dyn Storage) -> StdResult<Vec<SyncEvent>> {
+        match &self.history {
+            Some(history) => history.load(storage, self.storage_key(), Key::default()),
+            None => Ok(vec![]),
         }
     }
-    pub fn load(
+
+    pub fn load(&self, storage: &dyn Storage) -> SyncStateResult<T> {
+        self.item
+            .may_load(storage)?
+            .ok_or_else(|| SyncStateError::NotFound {
+                key: self.storage_key(),
+            })
+    }
+
+    pub fn may_load(&self, storage: &dyn Storage) -> SyncStateResult<Option<T>> {
+        self.item.may_load(storage).map_err(Into::into)
+    }
+
+    pub fn load_status(
         &self,
         storage: &dyn Storage,
-        key: (impl Into<StorageKey>, DataState),
+        state: DataState,
     ) -> SyncStateResult<StateChange> {
         self.state_status_map
-            .load(storage, (key.0.into(), key.1.to_num()))
+            .load(storage, (self.storage_key(), state.to_num()))
             .map_err(Into::into)
     }
 
+    pub fn data_state(&self, storage: &dyn Storage) -> Option<DataState> {
+        [DataState::Initiated, DataState::Proposed]
+            .into_iter()
+            .find(|state| {
+                self.state_status_map
+                    .has(storage, (self.storage_key(), state.to_num()))
+            })
+    }
+
     /// Load a state change
     /// Errors if no proposed state is found
-    pub fn load_state_change(
-        &self,
-        storage: &dyn Storage,
-        key: impl Into<StorageKey>,
-    ) -> SyncStateResult<StateChange> {
-        let key = key.into();
+    pub fn load_state_change(&self, storage: &dyn Storage) -> SyncStateResult<StateChange> {
         let state_change = self
-            .load(storage, (key.clone(), DataState::Initiated))
-            .or_else(|_| self.load(storage, (key.clone(), DataState::Proposed)))
+            .load_status(storage, DataState::Initiated)
+            .or_else(|_| self.load_status(storage, DataState::Proposed))
             .ok();
         state_change.ok_or(SyncStateError::NoProposedState)
     }
 
-    pub fn assert_finalized(
-        &self,
-        storage: &dyn Storage,
-        key: impl Into<StorageKey>,
-    ) -> SyncStateResult<()> {
-        let key = key.into();
-        if self
-            .state_status_map
-            .has(storage, (key.clone(), DataState::Initiated.to_num()))
-            || self
-                .state_status_map
-                .has(storage, (key.clone(), DataState::Proposed.to_num()))
-        {
+    /// Load the initiated or proposed value
+    pub fn load_pending(&self, storage: &dyn Storage) -> SyncStateResult<T> {
+        match self.load_state_change(storage)? {
+            StateChange::Proposal(value) => Ok(from_json(value)?),
+            // Backups are never stored in the pending slots
+            StateChange::Backup(_) => Err(SyncStateError::NoProposedState),
+        }
+    }
+
+    pub fn assert_finalized(&self, storage: &dyn Storage) -> SyncStateResult<()> {
+        if let Some(state) = self.data_state(storage) {
             return Err(SyncStateError::DataNotFinalized {
-                key,
-                state: "unknown".to_string(),
+                key: self.storage_key(),
+                state,
             });
         }
         Ok(())
     }
 
-    pub fn propose_item_state(
+    pub fn apply_ack(
         &self,
         storage: &mut dyn Storage,
-        key: impl Into<StorageKey>,
-        state: StateChange,
-    ) -> SyncStateResult<()> {
-        self.state_status_map
-            .save(storage, (key.into(), DataState::Proposed.to_num()), &state)
-            .map_err(Into::into)
+        chain: ChainName,
+    ) -> SyncStateResult<Option<SyncEvent>> {
+        let mut acks = self.outstanding_acks(storage)?;
+        // find chain in acks and remove it
+        let receipt_i = acks.iter().position(|c| c == &chain);
+        let ack_chain = match receipt_i {
+            Some(receipt_i) => acks.remove(receipt_i),
+            None => return Ok(None),
+        };
+
+        self.outstanding_acks
+            .save(storage, self.storage_key(), &acks)?;
+        let state = self.data_state(storage);
+        let event = self
+            .event(SyncAction::Ack, state.clone(), state)
+            .with_chain(ack_chain);
+        self.emit(storage, event).map(Some)
     }
 
-    pub fn initiate_item_state(
+    pub fn outstanding_acks(&self, storage: &dyn Storage) -> StdResult<Vec<ChainName>> {
+        Ok(self
+            .outstanding_acks
+            .may_load(storage, self.storage_key())?
+            .unwrap_or_default())
+    }
+
+    pub fn has_outstanding_acks(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(!self.outstanding_acks(storage)?.is_empty())
+    }
+
+    pub fn set_outstanding_acks(
         &self,
         storage: &mut dyn Storage,
-        key: impl Into<StorageKey>,
-        state: StateChange,
+        acks: Vec<ChainName>,
     ) -> SyncStateResult<()> {
-        self.state_status_map
-            .save(storage, (key.into(), DataState::Initiated.to_num()), &state)
-            .map_err(Into::into)
+        self.outstanding_acks
+            .save(storage, self.storage_key(), &acks)?;
+        Ok(())
     }
-    // Remove init / proposed states
-    pub fn finalize_item_state(
+
+    /// Propose a value, keeping the current value as backup
+    pub fn propose_item_state(
         &self,
         storage: &mut dyn Storage,
-        key: impl Into<StorageKey>,
-        set: bool,
-    ) -> SyncStateResult<()> {
-        let key = key.into();
-        // remove any of the states
-        if let Some(_change) = self
-            .state_status_map
-            .may_load(storage, (key.clone(), DataState::Initiated.to_num()))?
-        {
-            self.state_status_map
-                .remove(storage, (key.clone(), DataState::Initiated.to_num()));
-            Ok(())
-        } else if let Some(_change) = self
+        proposal_value: T,
+    ) -> SyncStateResult<SyncEvent> {
+        let from = self.data_state(storage);
+        // A value initiated here can't also be proposed by another chain
+        if let Some(DataState::Initiated) = from {
+            return Err(SyncStateError::DataNotFinalized {
+                key: self.storage_key(),
+                state: DataState::Initiated,
+            });
+        }
+        let backup = self.item.may_load(storage)?;
+        self.state_status_map.save(
+            storage,
+            (self.storage_key(), BACKUP_SLOT),
+            &StateChange::Backup(to_json_binary(&backup)?),
+        )?;
+        self.state_status_map.save(
+            storage,
+            (self.storage_key(), DataState::Proposed.to_num()),
+            &StateChange::Proposal(to_json_binary(&proposal_value)?),
+        )?;
+        let event = self.event(SyncAction::Propose, from, Some(DataState::Proposed));
+        self.emit(storage, event)
+    }
+
+    pub fn initiate_item_state(
+        &self,
+        storage: &mut dyn Storage,
+        initiated_value: T,
+        outstanding_acks: Vec<ChainName>,
+    ) -> SyncStateResult<SyncEvent> {
+        self.assert_finalized(storage)?;
+
+        self.state_status_map.save(
+            storage,
+            (self.storage_key(), DataState::Initiated.to_num()),
+            &StateChange::Proposal(to_json_binary(&initiated_value)?),
+        )?;
+        self.set_outstanding_acks(storage, outstanding_acks)?;
+        let event = self.event(SyncAction::Initiate, None, Some(DataState::Initiated));
+        self.emit(storage, event)
+    }
+
+    /// Value from before the proposal
+    pub fn load_backup(&self, storage: &dyn Storage) -> SyncStateResult<Option<T>> {
+        match self
             .state_status_map
-            .may_load(storage, (key.clone(), DataState::Proposed.to_num()))?
+            .may_load(storage, (self.storage_key(), BACKUP_SLOT))?
         {
+            Some(StateChange::Backup(backup)) => Ok(from_json(backup)?),
+            _ => Err(SyncStateError::NoProposedState),
+        }
+    }
+
+    /// Abort a proposed state and restore the value from before the proposal
+    pub fn revert_item_state(&self, storage: &mut dyn Storage) -> SyncStateResult<SyncEvent> {
+        let state = self.data_state(storage);
+        if state != Some(DataState::Proposed) {
+            return Err(SyncStateError::DataNotProposed {
+                key: self.storage_key(),
+                state,
+            });
+        }
+
+        match self.load_backup(storage)? {
+            Some(backup) => self.item.save(storage, &backup)?,
+            None => self.item.remove(storage),
+        }
+        self.clear_state(storage);
+        let event = self.event(SyncAction::Revert, Some(DataState::Proposed), None);
+        self.emit(storage, event)
+    }
+
+    /// Drop any initiated or proposed state without finalizing it
+    pub fn clear_state(&self, storage: &mut dyn Storage) {
+        for slot in [
+            DataState::Initiated.to_num(),
+            DataState::Proposed.to_num(),
+            BACKUP_SLOT,
+        ] {
             self.state_status_map
-                .remove(storage, (key.clone(), DataState::Proposed.to_num()));
-            return Ok(());
-        } else if !set {
-            return Err(SyncStateError::NoProposedState);
-        } else {
-            Ok(())
+                .remove(storage, (self.storage_key(), slot));
         }
     }
+
+    // Remove init / proposed states
+    // Errors if no proposed state is found or provided
+    pub fn finalize_item_state(
+        &self,
+        storage: &mut dyn Storage,
+        value: Option<T>,
+    ) -> SyncStateResult<SyncEvent> {
+        let from = self.data_state(storage);
+        let value = match value {
+            Some(value) => value,
+            None => self.load_pending(storage)?,
+        };
+
+        self.clear_state(storage);
+        self.item.save(storage, &value)?;
+        let event = self.event(SyncAction::Finalize, from, None);
+        self.emit(storage, event)
+    }
 }
@@ -1,22 +1,80 @@
 mod error;
+mod event;
 mod item;
 mod map;
+mod two_phase;
 
 use std::fmt::Display;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Binary;
 pub use error::SyncStateError;
+pub use event::{SyncAction, SyncEvent};
 pub use item::ItemStateSyncController;
-pub use map::MapStateSyncController;
+pub use map::{Batch, MapStateSyncController};
+pub use two_phase::{Packet, Phase, Step, Transaction, TwoPhaseCommit, TwoPhaseMsg, TwoPhaseVote};
+
+/// Default namespace of the data states of map controllers
+pub const MAP_DATA_NAMESPACE: &str = "map_data";
+/// Default namespace of the outstanding acks of map controllers
+pub const MAP_ACKS_NAMESPACE: &str = "map_acks";
+/// Default namespace of the batches of map controllers
+pub const MAP_BATCHES_NAMESPACE: &str = "map_batches";
+/// Default namespace of the data states of item controllers
+pub const ITEM_DATA_NAMESPACE: &str = "item_data";
+/// Default namespace of the outstanding acks of item controllers
+pub const ITEM_ACKS_NAMESPACE: &str = "item_acks";
+/// Default namespace of the transactions of two-phase commits
+pub const TWO_PHASE_NAMESPACE: &str = "two_phase";
 
 pub type SyncStateResult<T> = Result<T, SyncStateError>;
 
 pub type StorageKey = String;
 pub type Key = String;
+/// Identifies a batch of keys synced together
+pub type SyncId = String;
+
+/// Panics if any of the namespaces is used twice.
+/// Evaluate it in a constant to check the storage layout of a contract at compile time:
+/// ```
+/// const _: () = ibc_sync_state::assert_unique_namespaces(&[
+///     "members",
+///     ibc_sync_state::MAP_DATA_NAMESPACE,
+///     ibc_sync_state::MAP_ACKS_NAMESPACE,
+/// ]);
+/// ```
+pub const fn assert_unique_namespaces(namespaces: &[&str]) {
+    let mut i = 0;
+    while i < namespaces.len() {
+        let mut j = i + 1;
+        while j < namespaces.len() {
+            if const_str_eq(namespaces[i], namespaces[j]) {
+                panic!("storage namespace used more than once");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
 
 #[cw_serde]
 pub enum StateChange {
+    /// Value from before the proposal, `None` if there was none. Restored when the proposal is reverted.
     Backup(Binary),
     Proposal(Binary),
 }
@@ -49,6 +107,9 @@ impl DataState {
     }
 }
 
+/// Slot next to the data states that holds the value from before a proposal, see [`StateChange::Backup`].
+pub(crate) const BACKUP_SLOT: u8 = 2;
+
 impl Display for DataState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
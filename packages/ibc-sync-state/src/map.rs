@@ -1,18 +1,33 @@
 use abstract_adapter::objects::chain_name::ChainName;
-use cosmwasm_std::{from_json, to_json_binary, StdResult, Storage};
-use cw_storage_plus::{Item, Map, PrimaryKey};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, to_json_binary, Order, StdResult, Storage};
+use cw_storage_plus::{Map, PrimaryKey};
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{DataState, Key, StateChange, StorageKey, SyncStateError, SyncStateResult};
+use crate::event::SyncHistory;
+use crate::{
+    DataState, Key, StateChange, StorageKey, SyncAction, SyncEvent, SyncId, SyncStateError,
+    SyncStateResult, BACKUP_SLOT, MAP_ACKS_NAMESPACE, MAP_BATCHES_NAMESPACE, MAP_DATA_NAMESPACE,
+};
 
-pub const MAPS_DATA_STATE: Map<(StorageKey, Key, u8), StateChange> = Map::new("map_data");
-pub const OUTSTANDING_ACKS: Item<Vec<ChainName>> = Item::new("map_acks");
+pub const MAPS_DATA_STATE: Map<(StorageKey, Key, u8), StateChange> = Map::new(MAP_DATA_NAMESPACE);
+pub const OUTSTANDING_ACKS: Map<(StorageKey, Key), Vec<ChainName>> = Map::new(MAP_ACKS_NAMESPACE);
+pub const BATCHES: Map<(StorageKey, SyncId), Batch> = Map::new(MAP_BATCHES_NAMESPACE);
+
+/// Keys synced together under one sync id
+#[cw_serde]
+pub struct Batch {
+    pub keys: Vec<Key>,
+    /// Chains that still have to ack the whole batch
+    pub outstanding_acks: Vec<ChainName>,
+}
 
 pub struct MapStateSyncController<'a, K, V> {
     state_status_map: Map<'static, (StorageKey, Key, u8), StateChange>,
     map: Map<'a, K, V>,
-    outstanding_acks: Item<'static, Vec<ChainName>>,
-    // introduce outstanding acks
+    outstanding_acks: Map<'static, (StorageKey, Key), Vec<ChainName>>,
+    batches: Map<'static, (StorageKey, SyncId), Batch>,
+    history: Option<SyncHistory>,
 }
 
 impl<'a, K, V> MapStateSyncController<'a, K, V>
@@ -20,11 +35,38 @@ where
     V: Serialize + DeserializeOwned,
     K: PrimaryKey<'a> + Into<String> + Clone,
 {
+    /// Controller using the default namespaces, shared with all other map controllers
     pub const fn new(map: Map<'a, K, V>) -> Self {
         MapStateSyncController {
             state_status_map: MAPS_DATA_STATE,
             map,
             outstanding_acks: OUTSTANDING_ACKS,
+            batches: BATCHES,
+            history: None,
+        }
+    }
+
+    /// Controller that stores its data states, outstanding acks and batches under its own namespaces
+    pub const fn with_namespaces(
+        map: Map<'a, K, V>,
+        data_namespace: &'static str,
+        acks_namespace: &'static str,
+        batches_namespace: &'static str,
+    ) -> Self {
+        MapStateSyncController {
+            state_status_map: Map::new(data_namespace),
+            map,
+            outstanding_acks: Map::new(acks_namespace),
+            batches: Map::new(batches_namespace),
+            history: None,
+        }
+    }
+
+    /// Keep the last `limit` events of each key under the namespace
+    pub const fn with_history(self, namespace: &'static str, limit: u32) -> Self {
+        MapStateSyncController {
+            history: Some(SyncHistory::new(namespace, limit)),
+            ..self
         }
     }
 
@@ -77,8 +119,31 @@ where
         None
     }
 
+    /// Record the event in the history, if kept
+    pub(crate) fn emit(
+        &self,
+        storage: &mut dyn Storage,
+        event: SyncEvent,
+    ) -> SyncStateResult<SyncEvent> {
+        if let Some(history) = &self.history {
+            history.record(storage, &event)?;
+        }
+        Ok(event)
+    }
+
+    /// Last events of the key, empty if no history is kept
+    pub fn history(&self, storage: &dyn Storage, key: impl Into<Key>) -> StdResult<Vec<SyncEvent>> {
+        match &self.history {
+            Some(history) => history.load(storage, self.storage_key(), key.into()),
+            None => Ok(vec![]),
+        }
+    }
+
     pub fn load(&self, storage: &dyn Storage, key: K) -> SyncStateResult<V> {
-        self.map.load(storage, key).map_err(Into::into)
+        let k: Key = key.clone().into();
+        self.map
+            .may_load(storage, key)?
+            .ok_or(SyncStateError::NotFound { key: k })
     }
 
     pub fn may_load(&self, storage: &dyn Storage, key: K) -> SyncStateResult<Option<V>> {
@@ -88,9 +153,11 @@ where
     pub fn apply_ack(
         &self,
         storage: &mut dyn Storage,
+        key: impl Into<Key>,
         chain: ChainName,
-    ) -> SyncStateResult<Option<ChainName>> {
-        let mut acks = self.outstanding_acks.load(storage)?;
+    ) -> SyncStateResult<Option<SyncEvent>> {
+        let key = key.into();
+        let mut acks = self.load_outstanding_acks(storage, key.clone())?;
         // find chain in acks and remove it
         let receipt_i = acks.iter().position(|c| c == &chain);
         let ack_chain = match receipt_i {
@@ -98,19 +165,89 @@ where
             None => return Ok(None),
         };
 
-        self.outstanding_acks.save(storage, &acks)?;
-        Ok(Some(ack_chain))
+        self.save_outstanding_acks(storage, key.clone(), acks)?;
+        let event = SyncEvent::new(self.storage_key(), Some(key), SyncAction::Ack, None, None)
+            .with_chain(ack_chain);
+        self.emit(storage, event).map(Some)
     }
 
-    pub fn has_outstanding_acks(&self, storage: &dyn Storage) -> StdResult<bool> {
-        let acks = self.outstanding_acks.load(storage)?;
-        Ok(!acks.is_empty())
+    pub fn has_outstanding_acks(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<Key>,
+    ) -> StdResult<bool> {
+        Ok(!self.load_outstanding_acks(storage, key)?.is_empty())
+    }
+
+    fn load_outstanding_acks(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<Key>,
+    ) -> StdResult<Vec<ChainName>> {
+        Ok(self
+            .outstanding_acks
+            .may_load(storage, (self.storage_key(), key.into()))?
+            .unwrap_or_default())
+    }
+
+    fn save_outstanding_acks(
+        &self,
+        storage: &mut dyn Storage,
+        key: Key,
+        acks: Vec<ChainName>,
+    ) -> StdResult<()> {
+        if acks.is_empty() {
+            self.outstanding_acks
+                .remove(storage, (self.storage_key(), key));
+            return Ok(());
+        }
+        self.outstanding_acks
+            .save(storage, (self.storage_key(), key), &acks)
+    }
+
+    /// Stop waiting on the acks of every key
+    pub fn clear_outstanding_acks(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        let keys = self
+            .outstanding_acks
+            .prefix(self.storage_key())
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<Key>>>()?;
+        for key in keys {
+            self.outstanding_acks
+                .remove(storage, (self.storage_key(), key));
+        }
+        Ok(())
     }
 
     pub fn has(&self, storage: &dyn Storage, key: K) -> bool {
         self.map.has(storage, key)
     }
 
+    /// Keys of this map that currently have an initiated or proposed state
+    pub fn pending_keys(&self, storage: &dyn Storage) -> StdResult<Vec<Key>> {
+        let mut keys = self
+            .state_status_map
+            .sub_prefix(self.storage_key())
+            .keys(storage, None, None, Order::Ascending)
+            .map(|k| k.map(|(key, _state)| key))
+            .collect::<StdResult<Vec<Key>>>()?;
+        keys.dedup();
+        Ok(keys)
+    }
+
+    /// Drop any initiated or proposed state for the key without finalizing it
+    pub fn clear_state(&self, storage: &mut dyn Storage, key: impl Into<Key>) {
+        let key = key.into();
+        for slot in [
+            DataState::Initiated.to_num(),
+            DataState::Proposed.to_num(),
+            BACKUP_SLOT,
+        ] {
+            self.state_status_map
+                .remove(storage, (self.storage_key(), key.clone(), slot));
+        }
+    }
+
     /// Load a state change
     /// Errors if no proposed state is found
     pub fn load_state_change(
@@ -132,60 +269,91 @@ where
         key: impl Into<Key>,
     ) -> SyncStateResult<()> {
         let key = key.into();
-        if self.state_status_map.has(
-            storage,
-            (
-                self.storage_key(),
-                key.clone(),
-                DataState::Initiated.to_num(),
-            ),
-        ) || self.state_status_map.has(
-            storage,
-            (
-                self.storage_key(),
-                key.clone(),
-                DataState::Proposed.to_num(),
-            ),
-        ) {
-            return Err(SyncStateError::DataNotFinalized {
-                key,
-                state: "unknown".to_string(),
-            });
+        if let Some(state) = self.data_state(storage, key.clone()) {
+            return Err(SyncStateError::DataNotFinalized { key, state });
         }
         Ok(())
     }
 
     fn assert_proposed(&self, storage: &dyn Storage, key: impl Into<Key>) -> SyncStateResult<()> {
         let key = key.into();
-        if !self.state_status_map.has(
-            storage,
-            (
-                self.storage_key(),
-                key.clone(),
-                DataState::Proposed.to_num(),
-            ),
-        ) {
-            return Err(SyncStateError::DataNotProposed {
-                key,
-                state: "Unknown".to_string(),
-            });
+        let state = self.data_state(storage, key.clone());
+        if state != Some(DataState::Proposed) {
+            return Err(SyncStateError::DataNotProposed { key, state });
         }
         Ok(())
     }
 
+    /// Propose a value for the key, keeping the current value as backup
     pub fn propose_kv_state(
         &self,
         storage: &mut dyn Storage,
-        key: impl Into<Key>,
+        key: K,
         proposal_value: V,
-    ) -> SyncStateResult<()> {
-        self.state_status_map
-            .save(
-                storage,
-                (self.storage_key(), key.into(), DataState::Proposed.to_num()),
-                &StateChange::Proposal(to_json_binary(&proposal_value)?),
-            )
-            .map_err(Into::into)
+    ) -> SyncStateResult<SyncEvent> {
+        let k: Key = key.clone().into();
+        let from = self.data_state(storage, k.clone());
+        // A value initiated here can't also be proposed by another chain
+        if let Some(DataState::Initiated) = from {
+            return Err(SyncStateError::DataNotFinalized {
+                key: k,
+                state: DataState::Initiated,
+            });
+        }
+        let backup = self.map.may_load(storage, key)?;
+        self.state_status_map.save(
+            storage,
+            (self.storage_key(), k.clone(), BACKUP_SLOT),
+            &StateChange::Backup(to_json_binary(&backup)?),
+        )?;
+        self.state_status_map.save(
+            storage,
+            (self.storage_key(), k.clone(), DataState::Proposed.to_num()),
+            &StateChange::Proposal(to_json_binary(&proposal_value)?),
+        )?;
+        let event = SyncEvent::new(
+            self.storage_key(),
+            Some(k),
+            SyncAction::Propose,
+            from,
+            Some(DataState::Proposed),
+        );
+        self.emit(storage, event)
+    }
+
+    /// Value of the key from before it was proposed
+    pub fn load_backup(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<Key>,
+    ) -> SyncStateResult<Option<V>> {
+        match self
+            .state_status_map
+            .may_load(storage, (self.storage_key(), key.into(), BACKUP_SLOT))?
+        {
+            Some(StateChange::Backup(backup)) => Ok(from_json(backup)?),
+            _ => Err(SyncStateError::NoProposedState),
+        }
+    }
+
+    /// Abort a proposed state and restore the value from before the proposal
+    pub fn revert_kv_state(&self, storage: &mut dyn Storage, key: K) -> SyncStateResult<SyncEvent> {
+        let k: Key = key.clone().into();
+        self.assert_proposed(storage, k.clone())?;
+
+        match self.load_backup(storage, k.clone())? {
+            Some(backup) => self.map.save(storage, key, &backup)?,
+            None => self.map.remove(storage, key),
+        }
+        self.clear_state(storage, k.clone());
+        let event = SyncEvent::new(
+            self.storage_key(),
+            Some(k),
+            SyncAction::Revert,
+            Some(DataState::Proposed),
+            None,
+        );
+        self.emit(storage, event)
     }
 
     pub fn initiate_kv_state(
@@ -194,26 +362,48 @@ where
         key: impl Into<Key>,
         initiated_value: V,
         outstanding_acks: Vec<ChainName>,
-    ) -> SyncStateResult<()> {
+    ) -> SyncStateResult<SyncEvent> {
         let key = key.into();
 
         self.assert_finalized(storage, key.clone())?;
 
+        let event = self.save_initiated(storage, key.clone(), &initiated_value)?;
+        self.save_outstanding_acks(storage, key, outstanding_acks)?;
+        Ok(event)
+    }
+
+    pub(crate) fn save_initiated(
+        &self,
+        storage: &mut dyn Storage,
+        key: Key,
+        initiated_value: &V,
+    ) -> SyncStateResult<SyncEvent> {
         self.state_status_map.save(
             storage,
-            (self.storage_key(), key, DataState::Initiated.to_num()),
-            &StateChange::Proposal(to_json_binary(&initiated_value)?),
+            (
+                self.storage_key(),
+                key.clone(),
+                DataState::Initiated.to_num(),
+            ),
+            &StateChange::Proposal(to_json_binary(initiated_value)?),
         )?;
-        self.outstanding_acks.save(storage, &outstanding_acks)?;
-        Ok(())
+        let event = SyncEvent::new(
+            self.storage_key(),
+            Some(key),
+            SyncAction::Initiate,
+            None,
+            Some(DataState::Initiated),
+        );
+        self.emit(storage, event)
     }
 
     pub fn set_outstanding_finalization_acks(
         &self,
         storage: &mut dyn Storage,
+        key: impl Into<Key>,
         acks: Vec<ChainName>,
     ) -> SyncStateResult<()> {
-        self.outstanding_acks.save(storage, &acks)?;
+        self.save_outstanding_acks(storage, key.into(), acks)?;
         Ok(())
     }
 
@@ -224,8 +414,9 @@ where
         storage: &mut dyn Storage,
         key: K,
         value: Option<V>,
-    ) -> SyncStateResult<()> {
-        let k = key.clone().into();
+    ) -> SyncStateResult<SyncEvent> {
+        let k: Key = key.clone().into();
+        let from = self.data_state(storage, k.clone());
 
         let (value, was_set) = {
             if let Some(value) = value {
@@ -233,10 +424,8 @@ where
             } else {
                 let value: Result<V, _> = match self.load_state_change(storage, k.clone())? {
                     StateChange::Proposal(value) => Ok(from_json(value)?),
-                    _ => Err(SyncStateError::DataNotFinalized {
-                        key: k.to_string(),
-                        state: "Backup".to_string(),
-                    }),
+                    // Backups are never stored in the pending slots
+                    StateChange::Backup(_) => Err(SyncStateError::NoProposedState),
                 };
                 (value?, false)
             }
@@ -259,11 +448,207 @@ where
                 storage,
                 (self.storage_key(), k.clone(), DataState::Proposed.to_num()),
             );
+            self.state_status_map
+                .remove(storage, (self.storage_key(), k.clone(), BACKUP_SLOT));
         } else if !was_set {
             return Err(SyncStateError::NoProposedState);
         }
 
         self.map.save(storage, key, &value)?;
-        Ok(())
+        let event = SyncEvent::new(
+            self.storage_key(),
+            Some(k),
+            SyncAction::Finalize,
+            from,
+            None,
+        );
+        self.emit(storage, event)
+    }
+}
+
+// BATCHES
+
+impl<'a, K, V> MapStateSyncController<'a, K, V>
+where
+    V: Serialize + DeserializeOwned,
+    K: PrimaryKey<'a> + Into<String> + From<Key> + Clone,
+{
+    pub fn load_batch(
+        &self,
+        storage: &dyn Storage,
+        sync_id: impl Into<SyncId>,
+    ) -> SyncStateResult<Batch> {
+        let sync_id = sync_id.into();
+        self.batches
+            .may_load(storage, (self.storage_key(), sync_id.clone()))?
+            .ok_or(SyncStateError::UnknownBatch { sync_id })
+    }
+
+    fn create_batch(
+        &self,
+        storage: &mut dyn Storage,
+        sync_id: SyncId,
+        batch: &Batch,
+    ) -> SyncStateResult<()> {
+        if self
+            .batches
+            .has(storage, (self.storage_key(), sync_id.clone()))
+        {
+            return Err(SyncStateError::BatchExists { sync_id });
+        }
+        self.batches
+            .save(storage, (self.storage_key(), sync_id), batch)
+            .map_err(Into::into)
+    }
+
+    /// Initiate all the values under one sync id, acked as a whole by `outstanding_acks`.
+    /// Nothing is initiated if any of the keys is pending.
+    pub fn initiate_batch(
+        &self,
+        storage: &mut dyn Storage,
+        sync_id: impl Into<SyncId>,
+        values: Vec<(K, V)>,
+        outstanding_acks: Vec<ChainName>,
+    ) -> SyncStateResult<Vec<SyncEvent>> {
+        let keys: Vec<Key> = values.iter().map(|(key, _)| key.clone().into()).collect();
+        for (i, key) in keys.iter().enumerate() {
+            self.assert_finalized(storage, key.clone())?;
+            // The same key twice would be initiated twice
+            if keys[..i].contains(key) {
+                return Err(SyncStateError::DataNotFinalized {
+                    key: key.clone(),
+                    state: DataState::Initiated,
+                });
+            }
+        }
+
+        self.create_batch(
+            storage,
+            sync_id.into(),
+            &Batch {
+                keys,
+                outstanding_acks,
+            },
+        )?;
+        values
+            .into_iter()
+            .map(|(key, value)| self.save_initiated(storage, key.into(), &value))
+            .collect()
+    }
+
+    /// Propose all the values of a batch initiated by another chain.
+    /// Nothing is proposed if any of the keys is initiated here.
+    pub fn propose_batch(
+        &self,
+        storage: &mut dyn Storage,
+        sync_id: impl Into<SyncId>,
+        values: Vec<(K, V)>,
+    ) -> SyncStateResult<Vec<SyncEvent>> {
+        let keys: Vec<Key> = values.iter().map(|(key, _)| key.clone().into()).collect();
+        for key in &keys {
+            if let Some(DataState::Initiated) = self.data_state(storage, key.clone()) {
+                return Err(SyncStateError::DataNotFinalized {
+                    key: key.clone(),
+                    state: DataState::Initiated,
+                });
+            }
+        }
+
+        self.create_batch(
+            storage,
+            sync_id.into(),
+            &Batch {
+                keys,
+                outstanding_acks: vec![],
+            },
+        )?;
+        values
+            .into_iter()
+            .map(|(key, value)| self.propose_kv_state(storage, key, value))
+            .collect()
+    }
+
+    pub fn apply_batch_ack(
+        &self,
+        storage: &mut dyn Storage,
+        sync_id: impl Into<SyncId>,
+        chain: ChainName,
+    ) -> SyncStateResult<Option<SyncEvent>> {
+        let sync_id = sync_id.into();
+        let mut batch = self.load_batch(storage, sync_id.clone())?;
+        let Some(receipt_i) = batch.outstanding_acks.iter().position(|c| c == &chain) else {
+            return Ok(None);
+        };
+        let ack_chain = batch.outstanding_acks.remove(receipt_i);
+
+        self.batches
+            .save(storage, (self.storage_key(), sync_id.clone()), &batch)?;
+        let event = SyncEvent::new(
+            self.storage_key(),
+            Some(sync_id),
+            SyncAction::Ack,
+            None,
+            None,
+        )
+        .with_chain(ack_chain);
+        self.emit(storage, event).map(Some)
+    }
+
+    pub fn has_outstanding_batch_acks(
+        &self,
+        storage: &dyn Storage,
+        sync_id: impl Into<SyncId>,
+    ) -> SyncStateResult<bool> {
+        let batch = self.load_batch(storage, sync_id)?;
+        Ok(!batch.outstanding_acks.is_empty())
+    }
+
+    /// Finalize every key of the batch and drop it
+    pub fn finalize_batch(
+        &self,
+        storage: &mut dyn Storage,
+        sync_id: impl Into<SyncId>,
+    ) -> SyncStateResult<Vec<SyncEvent>> {
+        let sync_id = sync_id.into();
+        let batch = self.load_batch(storage, sync_id.clone())?;
+        let events = batch
+            .keys
+            .into_iter()
+            .map(|key| self.finalize_kv_state(storage, K::from(key), None))
+            .collect::<SyncStateResult<Vec<_>>>()?;
+        self.batches.remove(storage, (self.storage_key(), sync_id));
+        Ok(events)
+    }
+
+    /// Restore the proposed keys of the batch, drop the initiated ones, and drop the batch
+    pub fn revert_batch(
+        &self,
+        storage: &mut dyn Storage,
+        sync_id: impl Into<SyncId>,
+    ) -> SyncStateResult<Vec<SyncEvent>> {
+        let sync_id = sync_id.into();
+        let batch = self.load_batch(storage, sync_id.clone())?;
+        let mut events = vec![];
+        for key in batch.keys {
+            match self.data_state(storage, key.clone()) {
+                Some(DataState::Proposed) => {
+                    events.push(self.revert_kv_state(storage, K::from(key))?);
+                }
+                Some(DataState::Initiated) => {
+                    self.clear_state(storage, key.clone());
+                    let event = SyncEvent::new(
+                        self.storage_key(),
+                        Some(key),
+                        SyncAction::Revert,
+                        Some(DataState::Initiated),
+                        None,
+                    );
+                    events.push(self.emit(storage, event)?);
+                }
+                None => {}
+            }
+        }
+        self.batches.remove(storage, (self.storage_key(), sync_id));
+        Ok(events)
     }
 }
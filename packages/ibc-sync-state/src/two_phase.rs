@@ -0,0 +1,420 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw_storage_plus::{Map, PrimaryKey};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    DataState, Key, MapStateSyncController, StorageKey, SyncAction, SyncEvent, SyncStateError,
+    SyncStateResult, TWO_PHASE_NAMESPACE,
+};
+
+/// Messages sent by the coordinator of a transaction to its participants
+#[cw_serde]
+pub enum TwoPhaseMsg<K, V> {
+    /// Propose the value, the participant votes by accepting or failing the packet
+    Prepare { key: K, value: V },
+    /// Every participant prepared the value, apply it
+    Commit { key: K },
+    /// A participant refused the value, drop it
+    Abort { key: K },
+}
+
+/// A message addressed to a participant
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet<K, V> {
+    pub to: ChainName,
+    pub msg: TwoPhaseMsg<K, V>,
+}
+
+/// Packets to send and sync events caused by a step of the coordinator
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step<K, V> {
+    pub packets: Vec<Packet<K, V>>,
+    pub events: Vec<SyncEvent>,
+}
+
+impl<K, V> Step<K, V> {
+    fn new(packets: Vec<Packet<K, V>>, events: Vec<SyncEvent>) -> Self {
+        Step { packets, events }
+    }
+}
+
+/// Answer of a participant to a [`TwoPhaseMsg::Prepare`]
+#[cw_serde]
+pub enum TwoPhaseVote {
+    Commit,
+    Abort { reason: String },
+}
+
+/// The result of a prepare packet is the vote of the participant
+impl<T> From<Result<T, String>> for TwoPhaseVote {
+    fn from(result: Result<T, String>) -> Self {
+        match result {
+            Ok(_) => TwoPhaseVote::Commit,
+            Err(reason) => TwoPhaseVote::Abort { reason },
+        }
+    }
+}
+
+#[cw_serde]
+pub enum Phase {
+    /// Waiting for the votes of the participants
+    Preparing,
+    /// Every participant voted commit, the value is applied locally
+    Prepared,
+    /// Waiting for the participants to ack the commit
+    Committing,
+    /// Waiting for the participants that prepared to ack the abort
+    Aborting,
+}
+
+/// A transaction as seen by its coordinator
+#[cw_serde]
+pub struct Transaction {
+    pub phase: Phase,
+    pub participants: Vec<ChainName>,
+    /// Participants that did not vote yet
+    pub awaiting_votes: Vec<ChainName>,
+    /// Participants that did not ack the commit or abort yet
+    pub awaiting_acks: Vec<ChainName>,
+}
+
+impl Transaction {
+    fn is_complete(&self) -> bool {
+        matches!(self.phase, Phase::Committing | Phase::Aborting)
+            && self.awaiting_votes.is_empty()
+            && self.awaiting_acks.is_empty()
+    }
+}
+
+/// Two-phase commit of the values of a synced map.
+///
+/// COORDINATOR
+/// prepare: initiated, Prepare sent to the participants
+/// all votes commit: finalized, Prepared
+/// commit: Commit sent to the participants, done once all of them acked
+/// a vote abort: initiated state cleared, Abort sent to the participants that prepared
+///
+/// PARTICIPANT
+/// Prepare received: proposed, Backup kept
+/// Commit received: finalized
+/// Abort received: reverted to the Backup
+pub struct TwoPhaseCommit<'a, K, V> {
+    controller: MapStateSyncController<'a, K, V>,
+    transactions: Map<'static, (StorageKey, Key), Transaction>,
+}
+
+impl<'a, K, V> TwoPhaseCommit<'a, K, V>
+where
+    V: Serialize + DeserializeOwned + Clone,
+    K: PrimaryKey<'a> + Into<String> + Clone,
+{
+    /// Transactions are stored under the default namespace, shared with all other two-phase commits
+    pub const fn new(controller: MapStateSyncController<'a, K, V>) -> Self {
+        TwoPhaseCommit {
+            controller,
+            transactions: Map::new(TWO_PHASE_NAMESPACE),
+        }
+    }
+
+    /// Transactions are stored under their own namespace
+    pub const fn with_namespace(
+        controller: MapStateSyncController<'a, K, V>,
+        namespace: &'static str,
+    ) -> Self {
+        TwoPhaseCommit {
+            controller,
+            transactions: Map::new(namespace),
+        }
+    }
+
+    pub const fn controller(&self) -> &MapStateSyncController<'a, K, V> {
+        &self.controller
+    }
+
+    fn storage_key(&self) -> StorageKey {
+        std::str::from_utf8(self.controller.map().namespace())
+            .unwrap()
+            .to_string()
+    }
+
+    pub fn transaction(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<Key>,
+    ) -> SyncStateResult<Option<Transaction>> {
+        self.transactions
+            .may_load(storage, (self.storage_key(), key.into()))
+            .map_err(Into::into)
+    }
+
+    fn load_transaction(&self, storage: &dyn Storage, key: &Key) -> SyncStateResult<Transaction> {
+        self.transaction(storage, key.clone())?
+            .ok_or_else(|| SyncStateError::UnknownTransaction { key: key.clone() })
+    }
+
+    fn save_transaction(
+        &self,
+        storage: &mut dyn Storage,
+        key: Key,
+        transaction: &Transaction,
+    ) -> SyncStateResult<()> {
+        if transaction.is_complete() {
+            self.transactions.remove(storage, (self.storage_key(), key));
+            return Ok(());
+        }
+        self.transactions
+            .save(storage, (self.storage_key(), key), transaction)
+            .map_err(Into::into)
+    }
+
+    fn packets(participants: &[ChainName], msg: TwoPhaseMsg<K, V>) -> Vec<Packet<K, V>> {
+        participants
+            .iter()
+            .map(|to| Packet {
+                to: to.clone(),
+                msg: msg.clone(),
+            })
+            .collect()
+    }
+
+    // COORDINATOR
+
+    /// Start a transaction for the value, returns the prepare packets.
+    /// Without participants the value is finalized right away.
+    pub fn prepare(
+        &self,
+        storage: &mut dyn Storage,
+        key: K,
+        value: V,
+        participants: Vec<ChainName>,
+    ) -> SyncStateResult<Step<K, V>> {
+        let k: Key = key.clone().into();
+        // Participants of an earlier transaction might not have committed yet
+        if self
+            .transactions
+            .has(storage, (self.storage_key(), k.clone()))
+        {
+            return Err(SyncStateError::TransactionPending { key: k });
+        }
+        if participants.is_empty() {
+            self.controller.assert_finalized(storage, k)?;
+            let event = self
+                .controller
+                .finalize_kv_state(storage, key, Some(value))?;
+            return Ok(Step::new(vec![], vec![event]));
+        }
+
+        // The transaction tracks the participants, the acks of other keys are left alone
+        self.controller.assert_finalized(storage, k.clone())?;
+        let event = self.controller.save_initiated(storage, k.clone(), &value)?;
+        self.save_transaction(
+            storage,
+            k,
+            &Transaction {
+                phase: Phase::Preparing,
+                participants: participants.clone(),
+                awaiting_votes: participants.clone(),
+                awaiting_acks: vec![],
+            },
+        )?;
+        let packets = Self::packets(&participants, TwoPhaseMsg::Prepare { key, value });
+        Ok(Step::new(packets, vec![event]))
+    }
+
+    /// Record the vote of a participant.
+    /// The first abort vote aborts the transaction, returning the abort packets for the participants that prepared.
+    pub fn on_vote(
+        &self,
+        storage: &mut dyn Storage,
+        key: K,
+        chain: ChainName,
+        vote: TwoPhaseVote,
+    ) -> SyncStateResult<Step<K, V>> {
+        let k: Key = key.clone().into();
+        let mut tx = self.load_transaction(storage, &k)?;
+
+        let Some(i) = tx.awaiting_votes.iter().position(|c| c == &chain) else {
+            return Err(SyncStateError::UnexpectedParticipant {
+                key: k,
+                chain: chain.to_string(),
+            });
+        };
+        tx.awaiting_votes.remove(i);
+
+        let step = match (tx.phase.clone(), vote) {
+            (Phase::Preparing, TwoPhaseVote::Commit) => {
+                let mut events = vec![];
+                if tx.awaiting_votes.is_empty() {
+                    events.push(
+                        self.controller
+                            .finalize_kv_state(storage, key.clone(), None)?,
+                    );
+                    tx.phase = Phase::Prepared;
+                }
+                Step::new(vec![], events)
+            }
+            (Phase::Preparing, TwoPhaseVote::Abort { .. }) => {
+                self.controller.clear_state(storage, k.clone());
+                let event = self.controller.emit(
+                    storage,
+                    SyncEvent::new(
+                        self.storage_key(),
+                        Some(k.clone()),
+                        SyncAction::Revert,
+                        Some(DataState::Initiated),
+                        None,
+                    )
+                    .with_chain(chain.clone()),
+                )?;
+                // Everyone that voted before prepared the value
+                tx.awaiting_acks = tx
+                    .participants
+                    .iter()
+                    .filter(|c| *c != &chain && !tx.awaiting_votes.contains(c))
+                    .cloned()
+                    .collect();
+                tx.phase = Phase::Aborting;
+                let packets = Self::packets(&tx.awaiting_acks, TwoPhaseMsg::Abort { key });
+                Step::new(packets, vec![event])
+            }
+            // Late vote on an aborted transaction, the participant has to drop its value as well
+            (Phase::Aborting, TwoPhaseVote::Commit) => {
+                tx.awaiting_acks.push(chain.clone());
+                Step::new(Self::packets(&[chain], TwoPhaseMsg::Abort { key }), vec![])
+            }
+            (Phase::Aborting, TwoPhaseVote::Abort { .. }) => Step::new(vec![], vec![]),
+            (phase, _) => {
+                return Err(SyncStateError::InvalidPhase {
+                    key: k,
+                    phase: format!("{phase:?}"),
+                })
+            }
+        };
+
+        self.save_transaction(storage, k, &tx)?;
+        Ok(step)
+    }
+
+    /// Commit a prepared transaction, returns the commit packets
+    pub fn commit(&self, storage: &mut dyn Storage, key: K) -> SyncStateResult<Vec<Packet<K, V>>> {
+        let k: Key = key.clone().into();
+        let mut tx = self.load_transaction(storage, &k)?;
+        if tx.phase != Phase::Prepared {
+            return Err(SyncStateError::InvalidPhase {
+                key: k,
+                phase: format!("{:?}", tx.phase),
+            });
+        }
+
+        tx.phase = Phase::Committing;
+        tx.awaiting_acks.clone_from(&tx.participants);
+        self.save_transaction(storage, k, &tx)?;
+        Ok(Self::packets(&tx.participants, TwoPhaseMsg::Commit { key }))
+    }
+
+    /// Record the ack of a commit or abort packet.
+    /// Returns whether the transaction is complete.
+    pub fn on_ack(
+        &self,
+        storage: &mut dyn Storage,
+        key: K,
+        chain: ChainName,
+    ) -> SyncStateResult<bool> {
+        let k: Key = key.into();
+        let mut tx = self.load_transaction(storage, &k)?;
+
+        let Some(i) = tx.awaiting_acks.iter().position(|c| c == &chain) else {
+            return Err(SyncStateError::UnexpectedParticipant {
+                key: k,
+                chain: chain.to_string(),
+            });
+        };
+        tx.awaiting_acks.remove(i);
+
+        self.save_transaction(storage, k, &tx)?;
+        Ok(tx.is_complete())
+    }
+
+    /// Keys with an open transaction
+    pub fn pending_transactions(&self, storage: &dyn Storage) -> SyncStateResult<Vec<Key>> {
+        self.transactions
+            .prefix(self.storage_key())
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<Key>>>()
+            .map_err(Into::into)
+    }
+
+    /// Stop waiting on a participant, e.g. when it left.
+    /// A transaction that only waited on its vote becomes prepared.
+    pub fn drop_participant(
+        &self,
+        storage: &mut dyn Storage,
+        key: K,
+        chain: &ChainName,
+    ) -> SyncStateResult<Option<SyncEvent>> {
+        let k: Key = key.clone().into();
+        let mut tx = self.load_transaction(storage, &k)?;
+        tx.participants.retain(|c| c != chain);
+        tx.awaiting_votes.retain(|c| c != chain);
+        tx.awaiting_acks.retain(|c| c != chain);
+
+        let mut event = None;
+        if tx.phase == Phase::Preparing && tx.awaiting_votes.is_empty() {
+            event = Some(self.controller.finalize_kv_state(storage, key, None)?);
+            tx.phase = Phase::Prepared;
+        }
+        self.save_transaction(storage, k, &tx)?;
+        Ok(event)
+    }
+
+    /// Drop the transaction and any pending state of the key without notifying the participants
+    pub fn clear(&self, storage: &mut dyn Storage, key: impl Into<Key>) {
+        let key = key.into();
+        self.controller.clear_state(storage, key.clone());
+        self.transactions.remove(storage, (self.storage_key(), key));
+    }
+
+    // PARTICIPANT
+
+    /// Propose the value, errors (voting abort) if the key already has a pending state
+    pub fn on_prepare(
+        &self,
+        storage: &mut dyn Storage,
+        key: K,
+        value: V,
+    ) -> SyncStateResult<SyncEvent> {
+        self.controller.assert_finalized(storage, key.clone())?;
+        self.controller.propose_kv_state(storage, key, value)
+    }
+
+    pub fn on_commit(&self, storage: &mut dyn Storage, key: K) -> SyncStateResult<SyncEvent> {
+        self.controller.finalize_kv_state(storage, key, None)
+    }
+
+    /// Revert the proposed value, if the prepare was never applied there is nothing to do
+    pub fn on_abort(
+        &self,
+        storage: &mut dyn Storage,
+        key: K,
+    ) -> SyncStateResult<Option<SyncEvent>> {
+        if self.controller.data_state(storage, key.clone()) != Some(DataState::Proposed) {
+            return Ok(None);
+        }
+        self.controller.revert_kv_state(storage, key).map(Some)
+    }
+
+    /// Apply a message received from the coordinator
+    pub fn handle(
+        &self,
+        storage: &mut dyn Storage,
+        msg: TwoPhaseMsg<K, V>,
+    ) -> SyncStateResult<Option<SyncEvent>> {
+        match msg {
+            TwoPhaseMsg::Prepare { key, value } => self.on_prepare(storage, key, value).map(Some),
+            TwoPhaseMsg::Commit { key } => self.on_commit(storage, key).map(Some),
+            TwoPhaseMsg::Abort { key } => self.on_abort(storage, key),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::testing::MockStorage;
+use cw_storage_plus::Item;
+use ibc_sync_state::{DataState, ItemStateSyncController, SyncStateError, SyncStateResult};
+
+#[cw_serde]
+struct Config {
+    threshold: u8,
+}
+
+const CONFIG: Item<Config> = Item::new("config");
+const CONFIG_SYNC: ItemStateSyncController<Config> = ItemStateSyncController::new(CONFIG);
+
+#[test]
+fn initiate_ack_finalize() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let remote = ChainName::from_chain_id("juno-1");
+    CONFIG.save(&mut storage, &Config { threshold: 1 })?;
+
+    CONFIG_SYNC.initiate_item_state(&mut storage, Config { threshold: 2 }, vec![remote.clone()])?;
+    assert_eq!(CONFIG_SYNC.data_state(&storage), Some(DataState::Initiated));
+    assert!(CONFIG_SYNC.assert_finalized(&storage).is_err());
+    // The item is unchanged until finalized
+    assert_eq!(CONFIG_SYNC.load(&storage)?, Config { threshold: 1 });
+
+    // Can't initiate twice
+    assert_eq!(
+        CONFIG_SYNC.initiate_item_state(&mut storage, Config { threshold: 3 }, vec![]),
+        Err(SyncStateError::DataNotFinalized {
+            key: "config".to_string(),
+            state: DataState::Initiated
+        })
+    );
+
+    assert_eq!(
+        CONFIG_SYNC
+            .apply_ack(&mut storage, remote.clone())?
+            .and_then(|event| event.chain),
+        Some(remote)
+    );
+    assert!(!CONFIG_SYNC.has_outstanding_acks(&storage)?);
+
+    CONFIG_SYNC.finalize_item_state(&mut storage, None)?;
+    assert_eq!(CONFIG_SYNC.data_state(&storage), None);
+    assert_eq!(CONFIG_SYNC.load(&storage)?, Config { threshold: 2 });
+
+    // Nothing left to finalize
+    assert_eq!(
+        CONFIG_SYNC.finalize_item_state(&mut storage, None),
+        Err(SyncStateError::NoProposedState)
+    );
+    Ok(())
+}
+
+#[test]
+fn propose_finalize() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+
+    CONFIG_SYNC.propose_item_state(&mut storage, Config { threshold: 4 })?;
+    assert_eq!(CONFIG_SYNC.data_state(&storage), Some(DataState::Proposed));
+    assert_eq!(CONFIG_SYNC.load_pending(&storage)?, Config { threshold: 4 });
+    assert_eq!(CONFIG_SYNC.may_load(&storage)?, None);
+
+    CONFIG_SYNC.finalize_item_state(&mut storage, None)?;
+    assert_eq!(CONFIG_SYNC.load(&storage)?, Config { threshold: 4 });
+    Ok(())
+}
+
+#[test]
+fn propose_revert() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    CONFIG.save(&mut storage, &Config { threshold: 1 })?;
+
+    CONFIG_SYNC.propose_item_state(&mut storage, Config { threshold: 5 })?;
+    assert_eq!(
+        CONFIG_SYNC.load_backup(&storage)?,
+        Some(Config { threshold: 1 })
+    );
+
+    CONFIG_SYNC.revert_item_state(&mut storage)?;
+    assert_eq!(CONFIG_SYNC.data_state(&storage), None);
+    assert_eq!(CONFIG_SYNC.load(&storage)?, Config { threshold: 1 });
+    assert_eq!(
+        CONFIG_SYNC.load_backup(&storage),
+        Err(SyncStateError::NoProposedState)
+    );
+
+    // Only proposed states can be reverted
+    assert!(CONFIG_SYNC.revert_item_state(&mut storage).is_err());
+    Ok(())
+}
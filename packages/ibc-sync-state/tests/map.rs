@@ -0,0 +1,217 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::testing::MockStorage;
+use cw_storage_plus::Map;
+use ibc_sync_state::{
+    DataState, MapStateSyncController, SyncAction, SyncStateError, SyncStateResult,
+};
+
+const VALUES: Map<String, u64> = Map::new("values");
+const VALUES_SYNC: MapStateSyncController<String, u64> = MapStateSyncController::new(VALUES);
+const TRACKED_SYNC: MapStateSyncController<String, u64> =
+    MapStateSyncController::new(VALUES).with_history("history", 2);
+
+#[test]
+fn propose_keeps_backup() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let key = "a".to_string();
+
+    // New key, no previous value
+    VALUES_SYNC.propose_kv_state(&mut storage, key.clone(), 1)?;
+    assert_eq!(VALUES_SYNC.load_backup(&storage, key.clone())?, None);
+    VALUES_SYNC.finalize_kv_state(&mut storage, key.clone(), None)?;
+    assert_eq!(
+        VALUES_SYNC.load_backup(&storage, key.clone()),
+        Err(SyncStateError::NoProposedState)
+    );
+
+    // Existing key
+    VALUES_SYNC.propose_kv_state(&mut storage, key.clone(), 2)?;
+    assert_eq!(VALUES_SYNC.load_backup(&storage, key.clone())?, Some(1));
+    assert_eq!(
+        VALUES_SYNC.data_state(&storage, key.clone()),
+        Some(DataState::Proposed)
+    );
+    Ok(())
+}
+
+#[test]
+fn revert_restores_backup() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let (existing, new) = ("a".to_string(), "b".to_string());
+    VALUES.save(&mut storage, existing.clone(), &1)?;
+
+    VALUES_SYNC.propose_kv_state(&mut storage, existing.clone(), 2)?;
+    VALUES_SYNC.propose_kv_state(&mut storage, new.clone(), 3)?;
+
+    VALUES_SYNC.revert_kv_state(&mut storage, existing.clone())?;
+    VALUES_SYNC.revert_kv_state(&mut storage, new.clone())?;
+
+    assert_eq!(VALUES_SYNC.load(&storage, existing.clone())?, 1);
+    assert_eq!(VALUES_SYNC.may_load(&storage, new.clone())?, None);
+    assert_eq!(
+        VALUES_SYNC.load(&storage, new.clone()),
+        Err(SyncStateError::NotFound { key: new.clone() })
+    );
+    assert_eq!(VALUES_SYNC.data_state(&storage, existing.clone()), None);
+    assert_eq!(VALUES_SYNC.pending_keys(&storage)?, Vec::<String>::new());
+
+    // Nothing left to revert
+    assert_eq!(
+        VALUES_SYNC.revert_kv_state(&mut storage, existing.clone()),
+        Err(SyncStateError::DataNotProposed {
+            key: existing,
+            state: None
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn events() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let juno = ChainName::from_chain_id("juno-1");
+    let key = "a".to_string();
+
+    let event = VALUES_SYNC.initiate_kv_state(&mut storage, key.clone(), 1, vec![juno.clone()])?;
+    assert_eq!(event.namespace, "values");
+    assert_eq!(event.key, Some(key.clone()));
+    assert_eq!(event.action, SyncAction::Initiate);
+    assert_eq!((event.from, event.to), (None, Some(DataState::Initiated)));
+
+    let event = VALUES_SYNC
+        .apply_ack(&mut storage, key.clone(), juno.clone())?
+        .unwrap();
+    assert_eq!(event.key, Some(key.clone()));
+    assert_eq!(event.chain, Some(juno.clone()));
+    assert_eq!(
+        event
+            .to_attributes()
+            .into_iter()
+            .map(|attr| (attr.key, attr.value))
+            .collect::<Vec<_>>(),
+        vec![
+            ("sync_action".to_string(), "Ack".to_string()),
+            ("sync_namespace".to_string(), "values".to_string()),
+            ("sync_key".to_string(), key.clone()),
+            ("sync_from".to_string(), "Finalized".to_string()),
+            ("sync_to".to_string(), "Finalized".to_string()),
+            ("sync_chain".to_string(), juno.to_string()),
+        ]
+    );
+    // No pending ack
+    assert_eq!(
+        VALUES_SYNC.apply_ack(&mut storage, key.clone(), juno)?,
+        None
+    );
+
+    let event = VALUES_SYNC.finalize_kv_state(&mut storage, key.clone(), None)?;
+    assert_eq!(event.action, SyncAction::Finalize);
+    assert_eq!((event.from, event.to), (Some(DataState::Initiated), None));
+
+    // Without history nothing is kept
+    assert_eq!(VALUES_SYNC.history(&storage, key)?, vec![]);
+    Ok(())
+}
+
+#[test]
+fn bounded_history() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let (a, b) = ("a".to_string(), "b".to_string());
+
+    TRACKED_SYNC.propose_kv_state(&mut storage, a.clone(), 1)?;
+    TRACKED_SYNC.revert_kv_state(&mut storage, a.clone())?;
+    TRACKED_SYNC.propose_kv_state(&mut storage, b.clone(), 2)?;
+    TRACKED_SYNC.propose_kv_state(&mut storage, a.clone(), 3)?;
+    TRACKED_SYNC.finalize_kv_state(&mut storage, a.clone(), None)?;
+
+    // Only the last two events of the key are kept
+    let actions = TRACKED_SYNC
+        .history(&storage, a)?
+        .into_iter()
+        .map(|event| event.action)
+        .collect::<Vec<_>>();
+    assert_eq!(actions, vec![SyncAction::Propose, SyncAction::Finalize]);
+    assert_eq!(TRACKED_SYNC.history(&storage, b)?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn batch() -> SyncStateResult<()> {
+    let (mut coordinator, mut participant) = (MockStorage::new(), MockStorage::new());
+    let juno = ChainName::from_chain_id("juno-1");
+    let values = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+
+    let events =
+        VALUES_SYNC.initiate_batch(&mut coordinator, "sync", values.clone(), vec![juno.clone()])?;
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        VALUES_SYNC.initiate_batch(&mut coordinator, "sync", vec![], vec![]),
+        Err(SyncStateError::BatchExists {
+            sync_id: "sync".to_string()
+        })
+    );
+    VALUES_SYNC.propose_batch(&mut participant, "sync", values)?;
+    assert_eq!(
+        VALUES_SYNC.pending_keys(&participant)?,
+        vec!["a".to_string(), "b".to_string()]
+    );
+
+    // One ack for the whole batch, the acks of the keys are untouched
+    assert!(!VALUES_SYNC.has_outstanding_acks(&coordinator, "a")?);
+    let event = VALUES_SYNC
+        .apply_batch_ack(&mut coordinator, "sync", juno.clone())?
+        .unwrap();
+    assert_eq!(event.key, Some("sync".to_string()));
+    assert_eq!(event.chain, Some(juno));
+    assert!(!VALUES_SYNC.has_outstanding_batch_acks(&coordinator, "sync")?);
+
+    for storage in [&mut coordinator, &mut participant] {
+        VALUES_SYNC.finalize_batch(storage, "sync")?;
+        assert_eq!(VALUES_SYNC.load(storage, "a".to_string())?, 1);
+        assert_eq!(VALUES_SYNC.load(storage, "b".to_string())?, 2);
+        assert_eq!(
+            VALUES_SYNC.load_batch(storage, "sync"),
+            Err(SyncStateError::UnknownBatch {
+                sync_id: "sync".to_string()
+            })
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn batch_is_atomic() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    VALUES.save(&mut storage, "a".to_string(), &1)?;
+    VALUES_SYNC.initiate_kv_state(&mut storage, "b", 5, vec![])?;
+
+    // "b" is pending, "a" isn't initiated either
+    assert_eq!(
+        VALUES_SYNC.initiate_batch(
+            &mut storage,
+            "sync",
+            vec![("a".to_string(), 2), ("b".to_string(), 3)],
+            vec![]
+        ),
+        Err(SyncStateError::DataNotFinalized {
+            key: "b".to_string(),
+            state: DataState::Initiated
+        })
+    );
+    assert_eq!(VALUES_SYNC.data_state(&storage, "a"), None);
+
+    VALUES_SYNC.finalize_kv_state(&mut storage, "b".to_string(), None)?;
+    VALUES_SYNC.propose_batch(
+        &mut storage,
+        "sync",
+        vec![("a".to_string(), 2), ("b".to_string(), 3)],
+    )?;
+    let events = VALUES_SYNC.revert_batch(&mut storage, "sync")?;
+    assert!(events
+        .iter()
+        .all(|event| event.action == SyncAction::Revert));
+    assert_eq!(VALUES_SYNC.load(&storage, "a".to_string())?, 1);
+    assert_eq!(VALUES_SYNC.load(&storage, "b".to_string())?, 5);
+    assert_eq!(VALUES_SYNC.pending_keys(&storage)?, Vec::<String>::new());
+    Ok(())
+}
@@ -0,0 +1,489 @@
+//! Model-based tests: random sequences of sync operations are applied to simulated chains,
+//! each with its own storage, and checked against a reference model.
+
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::Storage;
+use cw_storage_plus::Map;
+use ibc_sync_state::{
+    DataState, MapStateSyncController, Phase, SyncStateResult, TwoPhaseCommit, TwoPhaseMsg,
+    MAP_ACKS_NAMESPACE,
+};
+use proptest::prelude::*;
+
+const CHAINS: usize = 3;
+const KEYS: [&str; 3] = ["a", "b", "c"];
+
+const VALUES: Map<String, u64> = Map::new("values");
+const VALUES_SYNC: MapStateSyncController<String, u64> = MapStateSyncController::new(VALUES);
+const VALUES_2PC: TwoPhaseCommit<String, u64> =
+    TwoPhaseCommit::new(MapStateSyncController::new(VALUES));
+/// Acks the controller still waits on per key, read from its storage
+const OUTSTANDING_ACKS: Map<(String, String), Vec<ChainName>> = Map::new(MAP_ACKS_NAMESPACE);
+
+fn chain(i: usize) -> ChainName {
+    ChainName::from_chain_id(&format!("chain{i}-1"))
+}
+
+fn key_at(i: usize) -> String {
+    KEYS[i].to_string()
+}
+
+/// At most one of the initiated and proposed states is set for a key
+fn assert_single_pending_state(storage: &dyn Storage, key: &str) {
+    let slots = [DataState::Initiated, DataState::Proposed]
+        .iter()
+        .filter(|state| {
+            VALUES_SYNC.state_status_map().has(
+                storage,
+                ("values".to_string(), key.to_string(), state.to_num()),
+            )
+        })
+        .count();
+    assert!(slots <= 1, "{key} has {slots} pending states");
+}
+
+// CONTROLLER
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    Initiated(u64),
+    Proposed { value: u64, backup: Option<u64> },
+}
+
+/// Reference model of a chain's controller
+#[derive(Debug, Default)]
+struct Model {
+    values: BTreeMap<String, u64>,
+    pending: BTreeMap<String, Pending>,
+    acks: BTreeMap<String, Vec<ChainName>>,
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Initiate {
+        chain: usize,
+        key: usize,
+        value: u64,
+        acks: Vec<usize>,
+    },
+    Propose {
+        chain: usize,
+        key: usize,
+        value: u64,
+    },
+    Ack {
+        chain: usize,
+        key: usize,
+        from: usize,
+    },
+    /// Acks delivered in any order, possibly more than once
+    Acks {
+        chain: usize,
+        key: usize,
+        from: Vec<usize>,
+    },
+    Finalize {
+        chain: usize,
+        key: usize,
+    },
+    Revert {
+        chain: usize,
+        key: usize,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let chain = 0..CHAINS;
+    let key = 0..KEYS.len();
+    prop_oneof![
+        (
+            chain.clone(),
+            key.clone(),
+            0..10u64,
+            proptest::collection::vec(0..CHAINS, 0..CHAINS)
+        )
+            .prop_map(|(chain, key, value, acks)| Op::Initiate {
+                chain,
+                key,
+                value,
+                acks
+            }),
+        (chain.clone(), key.clone(), 0..10u64).prop_map(|(chain, key, value)| Op::Propose {
+            chain,
+            key,
+            value
+        }),
+        (chain.clone(), key.clone(), 0..CHAINS).prop_map(|(chain, key, from)| Op::Ack {
+            chain,
+            key,
+            from
+        }),
+        (
+            chain.clone(),
+            key.clone(),
+            proptest::collection::vec(0..CHAINS, 1..2 * CHAINS)
+        )
+            .prop_map(|(chain, key, from)| Op::Acks { chain, key, from }),
+        (chain.clone(), key.clone()).prop_map(|(chain, key)| Op::Finalize { chain, key }),
+        (chain, key).prop_map(|(chain, key)| Op::Revert { chain, key }),
+    ]
+}
+
+fn apply(storage: &mut dyn Storage, model: &mut Model, op: Op) {
+    match op {
+        Op::Initiate {
+            key, value, acks, ..
+        } => {
+            let acks: Vec<ChainName> = acks.into_iter().map(chain).collect();
+            let res = VALUES_SYNC.initiate_kv_state(storage, key_at(key), value, acks.clone());
+            match model.pending.entry(key_at(key)) {
+                Entry::Occupied(_) => assert!(res.is_err()),
+                Entry::Vacant(entry) => {
+                    res.unwrap();
+                    entry.insert(Pending::Initiated(value));
+                    model.acks.insert(key_at(key), acks);
+                }
+            }
+        }
+        Op::Propose { key, value, .. } => {
+            let res = VALUES_SYNC.propose_kv_state(storage, key_at(key), value);
+            match model.pending.get(&key_at(key)) {
+                Some(Pending::Initiated(_)) => assert!(res.is_err()),
+                _ => {
+                    res.unwrap();
+                    let backup = model.values.get(&key_at(key)).copied();
+                    model
+                        .pending
+                        .insert(key_at(key), Pending::Proposed { value, backup });
+                }
+            }
+        }
+        Op::Ack { key, from, .. } => ack(storage, model, key, from),
+        Op::Acks { key, from, .. } => {
+            for from in from {
+                ack(storage, model, key, from);
+            }
+        }
+        Op::Finalize { key, .. } => {
+            let res = VALUES_SYNC.finalize_kv_state(storage, key_at(key), None);
+            match model.pending.remove(&key_at(key)) {
+                Some(Pending::Initiated(value) | Pending::Proposed { value, .. }) => {
+                    res.unwrap();
+                    model.values.insert(key_at(key), value);
+                }
+                None => assert!(res.is_err()),
+            }
+        }
+        Op::Revert { key, .. } => {
+            let res = VALUES_SYNC.revert_kv_state(storage, key_at(key));
+            match model.pending.get(&key_at(key)).cloned() {
+                Some(Pending::Proposed { backup, .. }) => {
+                    res.unwrap();
+                    model.pending.remove(&key_at(key));
+                    match backup {
+                        Some(backup) => model.values.insert(key_at(key), backup),
+                        None => model.values.remove(&key_at(key)),
+                    };
+                }
+                _ => assert!(res.is_err()),
+            }
+        }
+    }
+}
+
+/// Duplicate and unexpected acks are ignored instead of removing another chain's or key's ack
+fn ack(storage: &mut dyn Storage, model: &mut Model, key: usize, from: usize) {
+    let event = VALUES_SYNC
+        .apply_ack(storage, key_at(key), chain(from))
+        .unwrap();
+    let acks = model.acks.entry(key_at(key)).or_default();
+    match acks.iter().position(|c| c == &chain(from)) {
+        Some(i) => {
+            acks.remove(i);
+            let event = event.unwrap();
+            assert_eq!(event.key, Some(key_at(key)));
+            assert_eq!(event.chain, Some(chain(from)));
+        }
+        None => assert_eq!(event, None),
+    }
+    assert_outstanding_acks(storage, model);
+}
+
+/// The outstanding acks of each key only ever shrink by the acks the model expected
+fn assert_outstanding_acks(storage: &dyn Storage, model: &Model) {
+    for key in KEYS {
+        let outstanding = OUTSTANDING_ACKS
+            .may_load(storage, ("values".to_string(), key.to_string()))
+            .unwrap()
+            .unwrap_or_default();
+        let expected = model.acks.get(key).cloned().unwrap_or_default();
+        assert_eq!(outstanding.len(), expected.len());
+        for chain in &expected {
+            assert_eq!(
+                outstanding.iter().filter(|c| *c == chain).count(),
+                expected.iter().filter(|c| *c == chain).count()
+            );
+        }
+    }
+}
+
+fn assert_matches_model(storage: &dyn Storage, model: &Model) {
+    for key in KEYS {
+        assert_single_pending_state(storage, key);
+        assert_eq!(
+            VALUES_SYNC.may_load(storage, key.to_string()).unwrap(),
+            model.values.get(key).copied()
+        );
+        let state = match model.pending.get(key) {
+            Some(Pending::Initiated(_)) => Some(DataState::Initiated),
+            Some(Pending::Proposed { .. }) => Some(DataState::Proposed),
+            None => None,
+        };
+        assert_eq!(VALUES_SYNC.data_state(storage, key), state);
+        assert_eq!(
+            VALUES_SYNC.has_outstanding_acks(storage, key).unwrap(),
+            model.acks.get(key).is_some_and(|acks| !acks.is_empty())
+        );
+    }
+    assert_outstanding_acks(storage, model);
+}
+
+proptest! {
+    #[test]
+    fn controller_matches_model(ops in proptest::collection::vec(op(), 1..60)) {
+        let mut chains: Vec<(MockStorage, Model)> =
+            (0..CHAINS).map(|_| (MockStorage::new(), Model::default())).collect();
+
+        for op in ops {
+            let i = match op {
+                Op::Initiate { chain, .. }
+                | Op::Propose { chain, .. }
+                | Op::Ack { chain, .. }
+                | Op::Acks { chain, .. }
+                | Op::Finalize { chain, .. }
+                | Op::Revert { chain, .. } => chain,
+            };
+            let (storage, model) = &mut chains[i];
+            apply(storage, model, op);
+            assert_matches_model(storage, model);
+        }
+    }
+}
+
+// TWO-PHASE COMMIT
+
+#[derive(Debug, Clone)]
+enum NetworkOp {
+    Prepare {
+        coordinator: usize,
+        key: usize,
+        value: u64,
+    },
+    /// Deliver one of the packets in flight, in any order
+    Deliver {
+        packet: usize,
+    },
+    Commit {
+        coordinator: usize,
+        key: usize,
+    },
+    /// Deliver the ack of an already delivered commit or abort again
+    Replay {
+        ack: usize,
+    },
+}
+
+fn network_op() -> impl Strategy<Value = NetworkOp> {
+    prop_oneof![
+        1 => (0..CHAINS, 0..KEYS.len(), 0..10u64).prop_map(|(coordinator, key, value)| {
+            NetworkOp::Prepare {
+                coordinator,
+                key,
+                value,
+            }
+        }),
+        3 => (0..16usize).prop_map(|packet| NetworkOp::Deliver { packet }),
+        1 => (0..CHAINS, 0..KEYS.len())
+            .prop_map(|(coordinator, key)| NetworkOp::Commit { coordinator, key }),
+        1 => (0..16usize).prop_map(|ack| NetworkOp::Replay { ack }),
+    ]
+}
+
+struct InFlight {
+    from: usize,
+    to: usize,
+    msg: TwoPhaseMsg<String, u64>,
+}
+
+struct Network {
+    chains: Vec<MockStorage>,
+    in_flight: Vec<InFlight>,
+    /// Acks delivered to a coordinator for its current transaction on a key
+    delivered_acks: Vec<(usize, usize, String)>,
+}
+
+impl Network {
+    fn new() -> Self {
+        Network {
+            chains: (0..CHAINS).map(|_| MockStorage::new()).collect(),
+            in_flight: vec![],
+            delivered_acks: vec![],
+        }
+    }
+
+    fn send(&mut self, from: usize, packets: Vec<ibc_sync_state::Packet<String, u64>>) {
+        for packet in packets {
+            let to = (0..CHAINS).find(|i| chain(*i) == packet.to).unwrap();
+            self.in_flight.push(InFlight {
+                from,
+                to,
+                msg: packet.msg,
+            });
+        }
+    }
+
+    fn prepare(&mut self, coordinator: usize, key: usize, value: u64) {
+        let participants = (0..CHAINS)
+            .filter(|i| *i != coordinator)
+            .map(chain)
+            .collect();
+        // Refused while the key is pending on the coordinator
+        if let Ok(step) = VALUES_2PC.prepare(
+            &mut self.chains[coordinator],
+            key_at(key),
+            value,
+            participants,
+        ) {
+            self.delivered_acks
+                .retain(|(from, _, k)| (*from, k) != (coordinator, &key_at(key)));
+            self.send(coordinator, step.packets);
+        }
+    }
+
+    fn commit(&mut self, coordinator: usize, key: usize) -> bool {
+        // Only prepared transactions can be committed
+        match VALUES_2PC.commit(&mut self.chains[coordinator], key_at(key)) {
+            Ok(packets) => {
+                self.send(coordinator, packets);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn deliver(&mut self, i: usize) -> SyncStateResult<()> {
+        let InFlight { from, to, msg } = self.in_flight.remove(i);
+        match msg {
+            TwoPhaseMsg::Prepare { ref key, .. } => {
+                let key = key.clone();
+                // The result of the packet is the vote
+                let vote = VALUES_2PC
+                    .handle(&mut self.chains[to], msg.clone())
+                    .map_err(|e| e.to_string())
+                    .into();
+                let step = VALUES_2PC.on_vote(&mut self.chains[from], key, chain(to), vote)?;
+                self.send(from, step.packets);
+            }
+            TwoPhaseMsg::Commit { ref key } | TwoPhaseMsg::Abort { ref key } => {
+                let key = key.clone();
+                VALUES_2PC.handle(&mut self.chains[to], msg)?;
+                VALUES_2PC.on_ack(&mut self.chains[from], key.clone(), chain(to))?;
+                self.delivered_acks.push((from, to, key));
+            }
+        }
+        Ok(())
+    }
+
+    /// A duplicate ack is refused and leaves the acks the coordinator waits on untouched
+    fn replay(&mut self, i: usize) {
+        let (from, to, key) = self.delivered_acks[i].clone();
+        let awaiting = |storage: &MockStorage| {
+            VALUES_2PC
+                .transaction(storage, key.clone())
+                .unwrap()
+                .map(|tx| tx.awaiting_acks)
+        };
+        let before = awaiting(&self.chains[from]);
+        assert!(VALUES_2PC
+            .on_ack(&mut self.chains[from], key.clone(), chain(to))
+            .is_err());
+        assert_eq!(awaiting(&self.chains[from]), before);
+    }
+
+    /// Deliver everything and commit every prepared transaction
+    fn settle(&mut self) -> SyncStateResult<()> {
+        loop {
+            while !self.in_flight.is_empty() {
+                self.deliver(0)?;
+            }
+            let mut committed = false;
+            for coordinator in 0..CHAINS {
+                for key in 0..KEYS.len() {
+                    let tx = VALUES_2PC.transaction(&self.chains[coordinator], key_at(key))?;
+                    if tx.map(|tx| tx.phase) == Some(Phase::Prepared) {
+                        committed |= self.commit(coordinator, key);
+                    }
+                }
+            }
+            if !committed {
+                return Ok(());
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn two_phase_commit_agrees(ops in proptest::collection::vec(network_op(), 1..80)) {
+        let mut network = Network::new();
+
+        for op in ops {
+            match op {
+                NetworkOp::Prepare { coordinator, key, value } => {
+                    network.prepare(coordinator, key, value)
+                }
+                NetworkOp::Deliver { packet } => {
+                    if !network.in_flight.is_empty() {
+                        let i = packet % network.in_flight.len();
+                        network.deliver(i).unwrap();
+                    }
+                }
+                NetworkOp::Commit { coordinator, key } => {
+                    network.commit(coordinator, key);
+                }
+                NetworkOp::Replay { ack } => {
+                    if !network.delivered_acks.is_empty() {
+                        let i = ack % network.delivered_acks.len();
+                        network.replay(i);
+                    }
+                }
+            }
+            for storage in &network.chains {
+                for key in KEYS {
+                    assert_single_pending_state(storage, key);
+                    // Transactions track their participants, concurrent prepares share no acks
+                    prop_assert!(!VALUES_SYNC.has_outstanding_acks(storage, key).unwrap());
+                }
+            }
+        }
+
+        network.settle().unwrap();
+
+        // Once settled, every chain finalized the same values
+        for key in KEYS {
+            let values = network
+                .chains
+                .iter()
+                .map(|storage| VALUES_SYNC.may_load(storage, key.to_string()).unwrap())
+                .collect::<Vec<_>>();
+            prop_assert!(values.windows(2).all(|w| w[0] == w[1]), "{key}: {values:?}");
+            for storage in &network.chains {
+                prop_assert_eq!(VALUES_SYNC.data_state(storage, key), None);
+                prop_assert_eq!(VALUES_2PC.transaction(storage, key).unwrap(), None);
+            }
+        }
+    }
+}
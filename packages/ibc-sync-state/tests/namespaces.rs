@@ -0,0 +1,136 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::testing::MockStorage;
+use cw_storage_plus::Map;
+use ibc_sync_state::{
+    assert_unique_namespaces, Batch, DataState, MapStateSyncController, SyncStateResult,
+    ITEM_ACKS_NAMESPACE, ITEM_DATA_NAMESPACE, MAP_ACKS_NAMESPACE, MAP_BATCHES_NAMESPACE,
+    MAP_DATA_NAMESPACE,
+};
+
+const PROPOSALS: Map<String, String> = Map::new("props");
+const CONFIGS: Map<String, String> = Map::new("configs");
+
+const PROPOSALS_SYNC: MapStateSyncController<String, String> =
+    MapStateSyncController::new(PROPOSALS);
+const CONFIGS_SYNC: MapStateSyncController<String, String> =
+    MapStateSyncController::with_namespaces(
+        CONFIGS,
+        "configs_data",
+        "configs_acks",
+        "configs_batches",
+    );
+
+const _: () = assert_unique_namespaces(&[
+    "props",
+    "configs",
+    "configs_data",
+    "configs_acks",
+    "configs_batches",
+    MAP_DATA_NAMESPACE,
+    MAP_ACKS_NAMESPACE,
+    MAP_BATCHES_NAMESPACE,
+    ITEM_DATA_NAMESPACE,
+    ITEM_ACKS_NAMESPACE,
+]);
+
+#[test]
+fn controllers_with_own_namespaces_dont_share_acks() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let juno = ChainName::from_chain_id("juno-1");
+    let osmosis = ChainName::from_chain_id("osmosis-1");
+
+    PROPOSALS_SYNC.initiate_kv_state(&mut storage, "a", "prop".to_string(), vec![juno.clone()])?;
+    CONFIGS_SYNC.initiate_kv_state(
+        &mut storage,
+        "a",
+        "config".to_string(),
+        vec![osmosis.clone()],
+    )?;
+
+    // The same key is tracked separately
+    assert_eq!(
+        PROPOSALS_SYNC.data_state(&storage, "a"),
+        Some(DataState::Initiated)
+    );
+    assert_eq!(
+        CONFIGS_SYNC.data_state(&storage, "a"),
+        Some(DataState::Initiated)
+    );
+
+    // Acking one controller leaves the other untouched
+    assert_eq!(
+        PROPOSALS_SYNC.apply_ack(&mut storage, "a", osmosis.clone())?,
+        None
+    );
+    assert_eq!(
+        PROPOSALS_SYNC
+            .apply_ack(&mut storage, "a", juno)?
+            .and_then(|event| event.chain),
+        Some(ChainName::from_chain_id("juno-1"))
+    );
+    assert!(!PROPOSALS_SYNC.has_outstanding_acks(&storage, "a")?);
+    assert!(CONFIGS_SYNC.has_outstanding_acks(&storage, "a")?);
+
+    CONFIGS_SYNC.finalize_kv_state(&mut storage, "a".to_string(), None)?;
+    assert_eq!(CONFIGS_SYNC.data_state(&storage, "a"), None);
+    assert_eq!(
+        PROPOSALS_SYNC.data_state(&storage, "a"),
+        Some(DataState::Initiated)
+    );
+    Ok(())
+}
+
+#[test]
+fn controllers_with_own_namespaces_dont_share_batches() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let juno = ChainName::from_chain_id("juno-1");
+    let osmosis = ChainName::from_chain_id("osmosis-1");
+
+    PROPOSALS_SYNC.initiate_batch(
+        &mut storage,
+        "sync",
+        vec![("a".to_string(), "prop".to_string())],
+        vec![juno.clone()],
+    )?;
+    // The same sync id is free in the other controller
+    CONFIGS_SYNC.initiate_batch(
+        &mut storage,
+        "sync",
+        vec![("a".to_string(), "config".to_string())],
+        vec![osmosis.clone()],
+    )?;
+    assert!(has_batch(&storage, MAP_BATCHES_NAMESPACE, "props", "sync"));
+    assert!(has_batch(&storage, "configs_batches", "configs", "sync"));
+    assert!(!has_batch(
+        &storage,
+        MAP_BATCHES_NAMESPACE,
+        "configs",
+        "sync"
+    ));
+
+    assert_eq!(
+        CONFIGS_SYNC.apply_batch_ack(&mut storage, "sync", juno)?,
+        None
+    );
+    CONFIGS_SYNC.apply_batch_ack(&mut storage, "sync", osmosis)?;
+    assert!(!CONFIGS_SYNC.has_outstanding_batch_acks(&storage, "sync")?);
+    assert!(PROPOSALS_SYNC.has_outstanding_batch_acks(&storage, "sync")?);
+    Ok(())
+}
+
+/// Whether a batch of the map is stored under the namespace
+fn has_batch(storage: &MockStorage, namespace: &'static str, map: &str, sync_id: &str) -> bool {
+    Map::<(String, String), Batch>::new(namespace)
+        .has(storage, (map.to_string(), sync_id.to_string()))
+}
+
+#[test]
+#[should_panic(expected = "storage namespace used more than once")]
+fn duplicate_namespaces() {
+    assert_unique_namespaces(&["props", MAP_DATA_NAMESPACE, "configs", "props"]);
+}
+
+#[test]
+fn unique_namespaces() {
+    assert_unique_namespaces(&["prop", "props", "propss"]);
+}
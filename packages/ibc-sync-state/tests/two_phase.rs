@@ -0,0 +1,248 @@
+use abstract_adapter::objects::chain_name::ChainName;
+use cosmwasm_std::testing::MockStorage;
+use cw_storage_plus::Map;
+use ibc_sync_state::{
+    DataState, MapStateSyncController, Phase, SyncStateError, SyncStateResult, TwoPhaseCommit,
+    TwoPhaseMsg, TwoPhaseVote,
+};
+
+const VALUES: Map<String, u64> = Map::new("values");
+const VALUES_2PC: TwoPhaseCommit<String, u64> =
+    TwoPhaseCommit::new(MapStateSyncController::new(VALUES));
+
+fn chains() -> (ChainName, ChainName) {
+    (
+        ChainName::from_chain_id("juno-1"),
+        ChainName::from_chain_id("osmosis-1"),
+    )
+}
+
+#[test]
+fn commit() -> SyncStateResult<()> {
+    let (mut coordinator, mut participant) = (MockStorage::new(), MockStorage::new());
+    let (juno, osmosis) = chains();
+    let key = "a".to_string();
+
+    let packets = VALUES_2PC
+        .prepare(
+            &mut coordinator,
+            key.clone(),
+            1,
+            vec![juno.clone(), osmosis.clone()],
+        )?
+        .packets;
+    assert_eq!(packets.len(), 2);
+    assert_eq!(
+        packets[0].msg,
+        TwoPhaseMsg::Prepare {
+            key: key.clone(),
+            value: 1
+        }
+    );
+    VALUES_2PC.handle(&mut participant, packets[0].msg.clone())?;
+    assert_eq!(
+        VALUES_2PC
+            .controller()
+            .data_state(&participant, key.clone()),
+        Some(DataState::Proposed)
+    );
+
+    // Can't commit before every participant voted
+    assert!(VALUES_2PC.commit(&mut coordinator, key.clone()).is_err());
+
+    VALUES_2PC.on_vote(
+        &mut coordinator,
+        key.clone(),
+        juno.clone(),
+        TwoPhaseVote::Commit,
+    )?;
+    VALUES_2PC.on_vote(
+        &mut coordinator,
+        key.clone(),
+        osmosis.clone(),
+        Ok::<_, String>(()).into(),
+    )?;
+    let tx = VALUES_2PC.transaction(&coordinator, key.clone())?.unwrap();
+    assert_eq!(tx.phase, Phase::Prepared);
+    assert_eq!(VALUES_2PC.controller().load(&coordinator, key.clone())?, 1);
+
+    let packets = VALUES_2PC.commit(&mut coordinator, key.clone())?;
+    assert_eq!(packets.len(), 2);
+    VALUES_2PC.handle(&mut participant, packets[0].msg.clone())?;
+    assert_eq!(VALUES_2PC.controller().load(&participant, key.clone())?, 1);
+    assert_eq!(
+        VALUES_2PC
+            .controller()
+            .data_state(&participant, key.clone()),
+        None
+    );
+
+    assert!(!VALUES_2PC.on_ack(&mut coordinator, key.clone(), juno.clone())?);
+    // Acks are only expected once
+    assert_eq!(
+        VALUES_2PC.on_ack(&mut coordinator, key.clone(), juno),
+        Err(SyncStateError::UnexpectedParticipant {
+            key: key.clone(),
+            chain: "juno".to_string()
+        })
+    );
+    assert!(VALUES_2PC.on_ack(&mut coordinator, key.clone(), osmosis)?);
+    assert_eq!(VALUES_2PC.transaction(&coordinator, key)?, None);
+    Ok(())
+}
+
+#[test]
+fn abort() -> SyncStateResult<()> {
+    let mut coordinator = MockStorage::new();
+    let (mut juno_storage, mut osmosis_storage) = (MockStorage::new(), MockStorage::new());
+    let (juno, osmosis) = chains();
+    let key = "a".to_string();
+    VALUES.save(&mut juno_storage, key.clone(), &5)?;
+
+    let packets = VALUES_2PC
+        .prepare(
+            &mut coordinator,
+            key.clone(),
+            1,
+            vec![juno.clone(), osmosis.clone()],
+        )?
+        .packets;
+    VALUES_2PC.handle(&mut juno_storage, packets[0].msg.clone())?;
+    // The key is already pending on osmosis, it refuses the value
+    VALUES_2PC.on_prepare(&mut osmosis_storage, key.clone(), 3)?;
+    let refused = VALUES_2PC.handle(&mut osmosis_storage, packets[1].msg.clone());
+    assert!(refused.is_err());
+
+    let packets = VALUES_2PC
+        .on_vote(
+            &mut coordinator,
+            key.clone(),
+            osmosis,
+            refused.map_err(|e| e.to_string()).into(),
+        )?
+        .packets;
+    // Juno didn't vote yet, it gets its abort once it does
+    assert!(packets.is_empty());
+    assert_eq!(
+        VALUES_2PC
+            .controller()
+            .data_state(&coordinator, key.clone()),
+        None
+    );
+    assert_eq!(
+        VALUES_2PC
+            .controller()
+            .may_load(&coordinator, key.clone())?,
+        None
+    );
+
+    let packets = VALUES_2PC
+        .on_vote(
+            &mut coordinator,
+            key.clone(),
+            juno.clone(),
+            TwoPhaseVote::Commit,
+        )?
+        .packets;
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].to, juno);
+    assert_eq!(packets[0].msg, TwoPhaseMsg::Abort { key: key.clone() });
+
+    // Juno restores its previous value
+    VALUES_2PC.handle(&mut juno_storage, packets[0].msg.clone())?;
+    assert_eq!(VALUES_2PC.controller().load(&juno_storage, key.clone())?, 5);
+    assert_eq!(
+        VALUES_2PC
+            .controller()
+            .data_state(&juno_storage, key.clone()),
+        None
+    );
+
+    assert!(VALUES_2PC.on_ack(&mut coordinator, key.clone(), juno)?);
+    assert_eq!(VALUES_2PC.transaction(&coordinator, key)?, None);
+    Ok(())
+}
+
+#[test]
+fn drop_participant() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let (juno, osmosis) = chains();
+    let key = "a".to_string();
+
+    VALUES_2PC.prepare(
+        &mut storage,
+        key.clone(),
+        1,
+        vec![juno.clone(), osmosis.clone()],
+    )?;
+    VALUES_2PC.on_vote(&mut storage, key.clone(), juno, TwoPhaseVote::Commit)?;
+    assert_eq!(
+        VALUES_2PC.pending_transactions(&storage)?,
+        vec![key.clone()]
+    );
+
+    // Only the dropped participant was left to vote
+    VALUES_2PC.drop_participant(&mut storage, key.clone(), &osmosis)?;
+    let tx = VALUES_2PC.transaction(&storage, key.clone())?.unwrap();
+    assert_eq!(tx.phase, Phase::Prepared);
+    assert_eq!(VALUES_2PC.commit(&mut storage, key.clone())?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn without_participants() -> SyncStateResult<()> {
+    let mut storage = MockStorage::new();
+    let key = "a".to_string();
+
+    let packets = VALUES_2PC
+        .prepare(&mut storage, key.clone(), 1, vec![])?
+        .packets;
+    assert!(packets.is_empty());
+    assert_eq!(VALUES_2PC.controller().load(&storage, key.clone())?, 1);
+    assert_eq!(
+        VALUES_2PC.commit(&mut storage, key.clone()),
+        Err(SyncStateError::UnknownTransaction { key })
+    );
+    Ok(())
+}
+
+#[test]
+fn concurrent_prepares_keep_acks() -> SyncStateResult<()> {
+    let mut coordinator = MockStorage::new();
+    let (juno, osmosis) = chains();
+    let controller = VALUES_2PC.controller();
+
+    controller.initiate_kv_state(&mut coordinator, "a", 1, vec![juno.clone()])?;
+    controller.finalize_kv_state(&mut coordinator, "a".to_string(), None)?;
+    VALUES_2PC.prepare(&mut coordinator, "b".to_string(), 2, vec![osmosis.clone()])?;
+    VALUES_2PC.prepare(&mut coordinator, "c".to_string(), 3, vec![juno.clone()])?;
+
+    // The prepares of other keys leave the acks of "a" alone
+    assert!(controller.has_outstanding_acks(&coordinator, "a")?);
+    assert_eq!(controller.apply_ack(&mut coordinator, "a", osmosis)?, None);
+    assert!(controller
+        .apply_ack(&mut coordinator, "a", juno.clone())?
+        .is_some());
+    assert!(!controller.has_outstanding_acks(&coordinator, "a")?);
+
+    // Each transaction still waits on its own participant
+    VALUES_2PC.on_vote(
+        &mut coordinator,
+        "c".to_string(),
+        juno,
+        TwoPhaseVote::Commit,
+    )?;
+    assert_eq!(
+        VALUES_2PC
+            .transaction(&coordinator, "c")?
+            .map(|tx| tx.phase),
+        Some(Phase::Prepared)
+    );
+    assert_eq!(
+        VALUES_2PC
+            .transaction(&coordinator, "b")?
+            .map(|tx| tx.phase),
+        Some(Phase::Preparing)
+    );
+    Ok(())
+}